@@ -44,45 +44,105 @@ SEXP savvy_dump_fontdb_impl__impl(void) {
     return handle_result(res);
 }
 
-SEXP savvy_string2fill_family__impl(SEXP c_arg__text, SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__tolerance) {
-    SEXP res = savvy_string2fill_family__ffi(c_arg__text, c_arg__font_family, c_arg__font_weight, c_arg__font_style, c_arg__tolerance);
+SEXP savvy_FontFace_arrow_stream__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__line_width, SEXP c_arg__kind, SEXP c_arg__stream, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_arrow_stream__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__line_width, c_arg__kind, c_arg__stream, c_arg__transform);
     return handle_result(res);
 }
 
-SEXP savvy_string2fill_file__impl(SEXP c_arg__text, SEXP c_arg__font_file, SEXP c_arg__tolerance) {
-    SEXP res = savvy_string2fill_file__ffi(c_arg__text, c_arg__font_file, c_arg__tolerance);
+SEXP savvy_FontFace_boolean__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__shape_x, SEXP c_arg__shape_y, SEXP c_arg__shape_id, SEXP c_arg__op, SEXP c_arg__fill, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_boolean__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__shape_x, c_arg__shape_y, c_arg__shape_id, c_arg__op, c_arg__fill, c_arg__threads, c_arg__transform);
     return handle_result(res);
 }
 
-SEXP savvy_string2path_family__impl(SEXP c_arg__text, SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__tolerance) {
-    SEXP res = savvy_string2path_family__ffi(c_arg__text, c_arg__font_family, c_arg__font_weight, c_arg__font_style, c_arg__tolerance);
+SEXP savvy_FontFace_curves__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_curves__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__threads, c_arg__transform);
     return handle_result(res);
 }
 
-SEXP savvy_string2path_file__impl(SEXP c_arg__text, SEXP c_arg__font_file, SEXP c_arg__tolerance) {
-    SEXP res = savvy_string2path_file__ffi(c_arg__text, c_arg__font_file, c_arg__tolerance);
+SEXP savvy_FontFace_fill__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__fill_rule, SEXP c_arg__threads, SEXP c_arg__vertex_attributes, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_fill__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__fill_rule, c_arg__threads, c_arg__vertex_attributes, c_arg__transform);
     return handle_result(res);
 }
 
-SEXP savvy_string2stroke_family__impl(SEXP c_arg__text, SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__tolerance, SEXP c_arg__line_width) {
-    SEXP res = savvy_string2stroke_family__ffi(c_arg__text, c_arg__font_family, c_arg__font_weight, c_arg__font_style, c_arg__tolerance, c_arg__line_width);
+SEXP savvy_FontFace_from_family__impl(SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__synthetic) {
+    SEXP res = savvy_FontFace_from_family__ffi(c_arg__font_family, c_arg__font_weight, c_arg__font_style, c_arg__synthetic);
     return handle_result(res);
 }
 
-SEXP savvy_string2stroke_file__impl(SEXP c_arg__text, SEXP c_arg__font_file, SEXP c_arg__tolerance, SEXP c_arg__line_width) {
-    SEXP res = savvy_string2stroke_file__ffi(c_arg__text, c_arg__font_file, c_arg__tolerance, c_arg__line_width);
+SEXP savvy_FontFace_from_file__impl(SEXP c_arg__font_file) {
+    SEXP res = savvy_FontFace_from_file__ffi(c_arg__font_file);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_geojson__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_geojson__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__threads, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_geometry__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__format, SEXP c_arg__by_string, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_geometry__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__threads, c_arg__format, c_arg__by_string, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_mesh__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__line_width, SEXP c_arg__width_profile, SEXP c_arg__line_join, SEXP c_arg__line_cap, SEXP c_arg__miter_limit, SEXP c_arg__dash, SEXP c_arg__dash_offset, SEXP c_arg__stroke, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_mesh__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__line_width, c_arg__width_profile, c_arg__line_join, c_arg__line_cap, c_arg__miter_limit, c_arg__dash, c_arg__dash_offset, c_arg__stroke, c_arg__threads, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_model__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__depth, SEXP c_arg__bevel, SEXP c_arg__format, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_model__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__threads, c_arg__depth, c_arg__bevel, c_arg__format, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_offset__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__distance, SEXP c_arg__line_join, SEXP c_arg__fill, SEXP c_arg__union, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_offset__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__distance, c_arg__line_join, c_arg__fill, c_arg__union, c_arg__threads, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_path__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__relative_tolerance, SEXP c_arg__max_segment_length, SEXP c_arg__uniform, SEXP c_arg__fill_rule, SEXP c_arg__union, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_path__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__relative_tolerance, c_arg__max_segment_length, c_arg__uniform, c_arg__fill_rule, c_arg__union, c_arg__threads, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_points__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__n, SEXP c_arg__spacing, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_points__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__n, c_arg__spacing, c_arg__threads, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_skeleton__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__resolution, SEXP c_arg__prune, SEXP c_arg__simplify, SEXP c_arg__threads, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_skeleton__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__resolution, c_arg__prune, c_arg__simplify, c_arg__threads, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_stroke__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__line_width, SEXP c_arg__width_profile, SEXP c_arg__line_join, SEXP c_arg__line_cap, SEXP c_arg__miter_limit, SEXP c_arg__dash, SEXP c_arg__dash_offset, SEXP c_arg__threads, SEXP c_arg__vertex_attributes, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_stroke__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__line_width, c_arg__width_profile, c_arg__line_join, c_arg__line_cap, c_arg__miter_limit, c_arg__dash, c_arg__dash_offset, c_arg__threads, c_arg__vertex_attributes, c_arg__transform);
+    return handle_result(res);
+}
+
+SEXP savvy_FontFace_svg__impl(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__size, SEXP c_arg__transform) {
+    SEXP res = savvy_FontFace_svg__ffi(self__, c_arg__text, c_arg__x, c_arg__y, c_arg__tolerance, c_arg__threads, c_arg__size, c_arg__transform);
     return handle_result(res);
 }
 
 
 static const R_CallMethodDef CallEntries[] = {
     {"savvy_dump_fontdb_impl__impl", (DL_FUNC) &savvy_dump_fontdb_impl__impl, 0},
-    {"savvy_string2fill_family__impl", (DL_FUNC) &savvy_string2fill_family__impl, 5},
-    {"savvy_string2fill_file__impl", (DL_FUNC) &savvy_string2fill_file__impl, 3},
-    {"savvy_string2path_family__impl", (DL_FUNC) &savvy_string2path_family__impl, 5},
-    {"savvy_string2path_file__impl", (DL_FUNC) &savvy_string2path_file__impl, 3},
-    {"savvy_string2stroke_family__impl", (DL_FUNC) &savvy_string2stroke_family__impl, 6},
-    {"savvy_string2stroke_file__impl", (DL_FUNC) &savvy_string2stroke_file__impl, 4},
+    {"savvy_FontFace_arrow_stream__impl", (DL_FUNC) &savvy_FontFace_arrow_stream__impl, 9},
+    {"savvy_FontFace_boolean__impl", (DL_FUNC) &savvy_FontFace_boolean__impl, 12},
+    {"savvy_FontFace_curves__impl", (DL_FUNC) &savvy_FontFace_curves__impl, 7},
+    {"savvy_FontFace_fill__impl", (DL_FUNC) &savvy_FontFace_fill__impl, 9},
+    {"savvy_FontFace_from_family__impl", (DL_FUNC) &savvy_FontFace_from_family__impl, 4},
+    {"savvy_FontFace_from_file__impl", (DL_FUNC) &savvy_FontFace_from_file__impl, 1},
+    {"savvy_FontFace_geojson__impl", (DL_FUNC) &savvy_FontFace_geojson__impl, 7},
+    {"savvy_FontFace_geometry__impl", (DL_FUNC) &savvy_FontFace_geometry__impl, 9},
+    {"savvy_FontFace_mesh__impl", (DL_FUNC) &savvy_FontFace_mesh__impl, 15},
+    {"savvy_FontFace_model__impl", (DL_FUNC) &savvy_FontFace_model__impl, 10},
+    {"savvy_FontFace_offset__impl", (DL_FUNC) &savvy_FontFace_offset__impl, 11},
+    {"savvy_FontFace_path__impl", (DL_FUNC) &savvy_FontFace_path__impl, 12},
+    {"savvy_FontFace_points__impl", (DL_FUNC) &savvy_FontFace_points__impl, 9},
+    {"savvy_FontFace_skeleton__impl", (DL_FUNC) &savvy_FontFace_skeleton__impl, 10},
+    {"savvy_FontFace_stroke__impl", (DL_FUNC) &savvy_FontFace_stroke__impl, 15},
+    {"savvy_FontFace_svg__impl", (DL_FUNC) &savvy_FontFace_svg__impl, 8},
     {NULL, NULL, 0}
 };
 
@@ -1,7 +1,19 @@
 SEXP savvy_dump_fontdb_impl__ffi(void);
-SEXP savvy_string2fill_family__ffi(SEXP c_arg__text, SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__tolerance);
-SEXP savvy_string2fill_file__ffi(SEXP c_arg__text, SEXP c_arg__font_file, SEXP c_arg__tolerance);
-SEXP savvy_string2path_family__ffi(SEXP c_arg__text, SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__tolerance);
-SEXP savvy_string2path_file__ffi(SEXP c_arg__text, SEXP c_arg__font_file, SEXP c_arg__tolerance);
-SEXP savvy_string2stroke_family__ffi(SEXP c_arg__text, SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__tolerance, SEXP c_arg__line_width);
-SEXP savvy_string2stroke_file__ffi(SEXP c_arg__text, SEXP c_arg__font_file, SEXP c_arg__tolerance, SEXP c_arg__line_width);
+
+// methods and associated functions for FontFace
+SEXP savvy_FontFace_arrow_stream__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__line_width, SEXP c_arg__kind, SEXP c_arg__stream, SEXP c_arg__transform);
+SEXP savvy_FontFace_boolean__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__shape_x, SEXP c_arg__shape_y, SEXP c_arg__shape_id, SEXP c_arg__op, SEXP c_arg__fill, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_curves__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_fill__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__fill_rule, SEXP c_arg__threads, SEXP c_arg__vertex_attributes, SEXP c_arg__transform);
+SEXP savvy_FontFace_from_family__ffi(SEXP c_arg__font_family, SEXP c_arg__font_weight, SEXP c_arg__font_style, SEXP c_arg__synthetic);
+SEXP savvy_FontFace_from_file__ffi(SEXP c_arg__font_file);
+SEXP savvy_FontFace_geojson__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_geometry__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__format, SEXP c_arg__by_string, SEXP c_arg__transform);
+SEXP savvy_FontFace_mesh__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__line_width, SEXP c_arg__width_profile, SEXP c_arg__line_join, SEXP c_arg__line_cap, SEXP c_arg__miter_limit, SEXP c_arg__dash, SEXP c_arg__dash_offset, SEXP c_arg__stroke, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_model__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__depth, SEXP c_arg__bevel, SEXP c_arg__format, SEXP c_arg__transform);
+SEXP savvy_FontFace_offset__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__distance, SEXP c_arg__line_join, SEXP c_arg__fill, SEXP c_arg__union, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_path__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__relative_tolerance, SEXP c_arg__max_segment_length, SEXP c_arg__uniform, SEXP c_arg__fill_rule, SEXP c_arg__union, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_points__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__n, SEXP c_arg__spacing, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_skeleton__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__resolution, SEXP c_arg__prune, SEXP c_arg__simplify, SEXP c_arg__threads, SEXP c_arg__transform);
+SEXP savvy_FontFace_stroke__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__line_width, SEXP c_arg__width_profile, SEXP c_arg__line_join, SEXP c_arg__line_cap, SEXP c_arg__miter_limit, SEXP c_arg__dash, SEXP c_arg__dash_offset, SEXP c_arg__threads, SEXP c_arg__vertex_attributes, SEXP c_arg__transform);
+SEXP savvy_FontFace_svg__ffi(SEXP self__, SEXP c_arg__text, SEXP c_arg__x, SEXP c_arg__y, SEXP c_arg__tolerance, SEXP c_arg__threads, SEXP c_arg__size, SEXP c_arg__transform);
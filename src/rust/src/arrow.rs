@@ -0,0 +1,471 @@
+//! A minimal implementation of the producer side of [the Arrow C stream
+//! interface](https://arrow.apache.org/docs/format/CStreamInterface.html).
+//!
+//! This allows the consumers (e.g. the arrow and nanoarrow R packages) to
+//! receive the results as Arrow record batches without converting them into R
+//! vectors. Each string is converted only when the consumer requests the next
+//! batch, so the whole result doesn't need to be in memory at once.
+
+use std::ffi::{CString, c_char, c_int, c_void};
+
+use crate::builder::color_to_hex;
+use crate::result::{Column, PathTibble};
+
+const ARROW_FLAG_NULLABLE: i64 = 2;
+
+// errno.h
+const EIO: c_int = 5;
+
+#[repr(C)]
+pub struct ArrowSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut ArrowSchema,
+    dictionary: *mut ArrowSchema,
+    release: Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    private_data: *mut c_void,
+}
+
+#[repr(C)]
+pub struct ArrowArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut ArrowArray,
+    dictionary: *mut ArrowArray,
+    release: Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    private_data: *mut c_void,
+}
+
+#[repr(C)]
+pub struct ArrowArrayStream {
+    get_schema: Option<unsafe extern "C" fn(*mut ArrowArrayStream, *mut ArrowSchema) -> c_int>,
+    get_next: Option<unsafe extern "C" fn(*mut ArrowArrayStream, *mut ArrowArray) -> c_int>,
+    get_last_error: Option<unsafe extern "C" fn(*mut ArrowArrayStream) -> *const c_char>,
+    release: Option<unsafe extern "C" fn(*mut ArrowArrayStream)>,
+    private_data: *mut c_void,
+}
+
+impl ArrowArrayStream {
+    /// Returns `true` if the stream is released or not initialized yet.
+    pub fn is_released(&self) -> bool {
+        self.release.is_none()
+    }
+}
+
+// Schema
+
+struct SchemaPrivate {
+    _format: CString,
+    _name: CString,
+    children: Vec<*mut ArrowSchema>,
+}
+
+impl ArrowSchema {
+    fn new(format: &str, name: &str, flags: i64, children: Vec<ArrowSchema>) -> Self {
+        let format = CString::new(format).unwrap();
+        let name = CString::new(name).unwrap();
+        let mut children: Vec<*mut ArrowSchema> = children
+            .into_iter()
+            .map(|c| Box::into_raw(Box::new(c)))
+            .collect();
+
+        let schema = Self {
+            format: format.as_ptr(),
+            name: name.as_ptr(),
+            metadata: std::ptr::null(),
+            flags,
+            n_children: children.len() as _,
+            children: children.as_mut_ptr(),
+            dictionary: std::ptr::null_mut(),
+            release: Some(release_schema),
+            private_data: std::ptr::null_mut(),
+        };
+
+        let private = Box::new(SchemaPrivate {
+            _format: format,
+            _name: name,
+            children,
+        });
+
+        Self {
+            private_data: Box::into_raw(private) as _,
+            ..schema
+        }
+    }
+}
+
+unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+    let schema = unsafe { &mut *schema };
+    let private = unsafe { Box::from_raw(schema.private_data as *mut SchemaPrivate) };
+    for &child in &private.children {
+        // The consumer might have moved the child; in that case, the release
+        // callback is marked as released.
+        unsafe {
+            if let Some(release) = (*child).release {
+                release(child);
+            }
+            drop(Box::from_raw(child));
+        }
+    }
+    schema.release = None;
+}
+
+// Array
+
+/// The owned data of a buffer.
+enum Buffer {
+    Real(Vec<f64>),
+    Integer(Vec<i32>),
+    Bytes(Vec<u8>),
+}
+
+impl Buffer {
+    fn as_ptr(&self) -> *const c_void {
+        match self {
+            Buffer::Real(v) => v.as_ptr() as _,
+            Buffer::Integer(v) => v.as_ptr() as _,
+            Buffer::Bytes(v) => v.as_ptr() as _,
+        }
+    }
+}
+
+struct ArrayPrivate {
+    _data: Vec<Option<Buffer>>,
+    _buffers: Vec<*const c_void>,
+    children: Vec<*mut ArrowArray>,
+}
+
+impl ArrowArray {
+    fn new(
+        length: usize,
+        null_count: usize,
+        data: Vec<Option<Buffer>>,
+        children: Vec<ArrowArray>,
+    ) -> Self {
+        let mut buffers: Vec<*const c_void> = data
+            .iter()
+            .map(|b| b.as_ref().map_or(std::ptr::null(), |b| b.as_ptr()))
+            .collect();
+        let mut children: Vec<*mut ArrowArray> = children
+            .into_iter()
+            .map(|c| Box::into_raw(Box::new(c)))
+            .collect();
+
+        let array = Self {
+            length: length as _,
+            null_count: null_count as _,
+            offset: 0,
+            n_buffers: buffers.len() as _,
+            n_children: children.len() as _,
+            buffers: buffers.as_mut_ptr(),
+            children: children.as_mut_ptr(),
+            dictionary: std::ptr::null_mut(),
+            release: Some(release_array),
+            private_data: std::ptr::null_mut(),
+        };
+
+        let private = Box::new(ArrayPrivate {
+            _data: data,
+            _buffers: buffers,
+            children,
+        });
+
+        Self {
+            private_data: Box::into_raw(private) as _,
+            ..array
+        }
+    }
+
+    /// A released array, which marks the end of the stream.
+    fn released() -> Self {
+        Self {
+            length: 0,
+            null_count: 0,
+            offset: 0,
+            n_buffers: 0,
+            n_children: 0,
+            buffers: std::ptr::null_mut(),
+            children: std::ptr::null_mut(),
+            dictionary: std::ptr::null_mut(),
+            release: None,
+            private_data: std::ptr::null_mut(),
+        }
+    }
+}
+
+unsafe extern "C" fn release_array(array: *mut ArrowArray) {
+    let array = unsafe { &mut *array };
+    let private = unsafe { Box::from_raw(array.private_data as *mut ArrayPrivate) };
+    for &child in &private.children {
+        unsafe {
+            if let Some(release) = (*child).release {
+                release(child);
+            }
+            drop(Box::from_raw(child));
+        }
+    }
+    // The buffers are freed here.
+    drop(private);
+    array.release = None;
+}
+
+/// Packs the booleans into a bitmap in the least-significant bit order.
+fn to_bitmap(values: impl ExactSizeIterator<Item = bool>) -> Vec<u8> {
+    let mut out = vec![0u8; values.len().div_ceil(8)];
+    for (i, v) in values.enumerate() {
+        if v {
+            out[i / 8] |= 1 << (i % 8);
+        }
+    }
+    out
+}
+
+fn to_string_buffers<S: AsRef<str>>(values: &[S]) -> (Buffer, Buffer) {
+    let mut offsets = Vec::with_capacity(values.len() + 1);
+    let mut data = Vec::new();
+    offsets.push(0);
+    for s in values {
+        data.extend_from_slice(s.as_ref().as_bytes());
+        offsets.push(data.len() as i32);
+    }
+    (Buffer::Integer(offsets), Buffer::Bytes(data))
+}
+
+impl Column {
+    /// The format string and the flags of the Arrow type.
+    fn arrow_type(&self) -> (&'static str, i64) {
+        match self {
+            Column::Real(_) => ("g", 0),
+            Column::NullableReal(_) => ("g", ARROW_FLAG_NULLABLE),
+            Column::Integer(_) => ("i", 0),
+            Column::Logical(_) => ("b", 0),
+            Column::Character(_) | Column::StaticCharacter(_) => ("u", 0),
+        }
+    }
+
+    fn into_arrow_array(self) -> ArrowArray {
+        match self {
+            Column::Real(v) => {
+                ArrowArray::new(v.len(), 0, vec![None, Some(Buffer::Real(v))], vec![])
+            }
+            Column::NullableReal(v) => {
+                let null_count = v.iter().filter(|v| v.is_none()).count();
+                let validity = to_bitmap(v.iter().map(|v| v.is_some()));
+                let values: Vec<f64> = v.iter().map(|v| v.unwrap_or(0.)).collect();
+                ArrowArray::new(
+                    v.len(),
+                    null_count,
+                    vec![Some(Buffer::Bytes(validity)), Some(Buffer::Real(values))],
+                    vec![],
+                )
+            }
+            Column::Integer(v) => {
+                ArrowArray::new(v.len(), 0, vec![None, Some(Buffer::Integer(v))], vec![])
+            }
+            Column::Logical(v) => {
+                let bitmap = to_bitmap(v.iter().copied());
+                ArrowArray::new(v.len(), 0, vec![None, Some(Buffer::Bytes(bitmap))], vec![])
+            }
+            Column::Character(v) => {
+                let (offsets, data) = to_string_buffers(&v);
+                ArrowArray::new(v.len(), 0, vec![None, Some(offsets), Some(data)], vec![])
+            }
+            Column::StaticCharacter(v) => {
+                let (offsets, data) = to_string_buffers(&v);
+                ArrowArray::new(v.len(), 0, vec![None, Some(offsets), Some(data)], vec![])
+            }
+        }
+    }
+}
+
+// Stream
+
+type ConvertFn = dyn FnMut(&str, lyon::math::Transform) -> savvy::Result<PathTibble>;
+
+/// Offsets to make the IDs unique across the batches.
+#[derive(Default)]
+struct IdOffsets {
+    glyph_id: i32,
+    path_id: i32,
+    shape_id: i32,
+    triangle_id: i32,
+}
+
+impl IdOffsets {
+    fn shift(&mut self, result: &mut PathTibble) {
+        fn shift_ids(ids: &mut [i32], offset: &mut i32, next: impl Fn(i32) -> i32) {
+            ids.iter_mut().for_each(|i| *i += *offset);
+            if let Some(&max) = ids.iter().max() {
+                *offset = next(max);
+            }
+        }
+
+        shift_ids(&mut result.glyph_id, &mut self.glyph_id, |i| i);
+        if let Some(path_id) = &mut result.path_id {
+            shift_ids(path_id, &mut self.path_id, |i| i);
+        }
+        if let Some(shape_id) = &mut result.shape_id {
+            shift_ids(shape_id, &mut self.shape_id, |i| i);
+        }
+        // triangle_id is 0-based
+        if let Some(triangle_id) = &mut result.triangle_id {
+            shift_ids(triangle_id, &mut self.triangle_id, |i| i + 1);
+        }
+    }
+}
+
+/// A stream of the results of each string. Each batch has `string_id` column.
+pub struct PathTibbleStream {
+    jobs: std::vec::IntoIter<(String, lyon::math::Transform)>,
+    convert: Box<ConvertFn>,
+    // Whether the batches have `color` column. Since the schema must be the
+    // same across the batches, this is determined by the font, not by the
+    // glyphs.
+    has_color: bool,
+    string_id: i32,
+    offsets: IdOffsets,
+    // The names and the types of the columns.
+    schema: Vec<(&'static str, &'static str, i64)>,
+    last_error: CString,
+}
+
+impl PathTibbleStream {
+    pub fn new(
+        jobs: Vec<(String, lyon::math::Transform)>,
+        has_color: bool,
+        mut convert: Box<ConvertFn>,
+    ) -> savvy::Result<Self> {
+        // Convert an empty string to know the columns.
+        let prototype = convert("", lyon::math::Transform::identity())?;
+
+        let mut stream = Self {
+            jobs: jobs.into_iter(),
+            convert,
+            has_color,
+            string_id: 0,
+            offsets: IdOffsets::default(),
+            schema: Vec::new(),
+            last_error: CString::default(),
+        };
+
+        stream.schema = stream
+            .normalize(prototype)
+            .into_columns()
+            .iter()
+            .map(|(name, column)| {
+                let (format, flags) = column.arrow_type();
+                (*name, format, flags)
+            })
+            .collect();
+
+        Ok(stream)
+    }
+
+    /// Adds `string_id` column, and adds or removes `color` column.
+    fn normalize(&self, mut result: PathTibble) -> PathTibble {
+        let n = result.x.len();
+        result.string_id = Some(vec![self.string_id; n]);
+        result.color = match (self.has_color, result.color) {
+            (true, Some(color)) => Some(color),
+            (true, None) => Some(vec![color_to_hex(None); n]),
+            (false, _) => None,
+        };
+        result
+    }
+
+    fn schema(&self) -> ArrowSchema {
+        let children = self
+            .schema
+            .iter()
+            .map(|(name, format, flags)| ArrowSchema::new(format, name, *flags, vec![]))
+            .collect();
+        ArrowSchema::new("+s", "", 0, children)
+    }
+
+    fn next_batch(&mut self) -> savvy::Result<Option<ArrowArray>> {
+        let Some((text, transform)) = self.jobs.next() else {
+            return Ok(None);
+        };
+
+        self.string_id += 1;
+        let mut result = (self.convert)(&text, transform)?;
+        self.offsets.shift(&mut result);
+        let result = self.normalize(result);
+
+        let n = result.x.len();
+        let children = result
+            .into_columns()
+            .into_iter()
+            .map(|(_, column)| column.into_arrow_array())
+            .collect();
+
+        Ok(Some(ArrowArray::new(n, 0, vec![None], children)))
+    }
+
+    /// Moves the stream into `out`. The consumer is responsible for releasing
+    /// it.
+    ///
+    /// # Safety
+    ///
+    /// `out` must point to a valid `ArrowArrayStream` struct.
+    pub unsafe fn export(self, out: *mut ArrowArrayStream) {
+        let stream = ArrowArrayStream {
+            get_schema: Some(stream_get_schema),
+            get_next: Some(stream_get_next),
+            get_last_error: Some(stream_get_last_error),
+            release: Some(stream_release),
+            private_data: Box::into_raw(Box::new(self)) as _,
+        };
+        unsafe { std::ptr::write(out, stream) };
+    }
+}
+
+unsafe fn stream_private<'a>(stream: *mut ArrowArrayStream) -> &'a mut PathTibbleStream {
+    unsafe { &mut *((*stream).private_data as *mut PathTibbleStream) }
+}
+
+unsafe extern "C" fn stream_get_schema(
+    stream: *mut ArrowArrayStream,
+    out: *mut ArrowSchema,
+) -> c_int {
+    let private = unsafe { stream_private(stream) };
+    unsafe { std::ptr::write(out, private.schema()) };
+    0
+}
+
+unsafe extern "C" fn stream_get_next(stream: *mut ArrowArrayStream, out: *mut ArrowArray) -> c_int {
+    let private = unsafe { stream_private(stream) };
+
+    match private.next_batch() {
+        Ok(array) => {
+            unsafe { std::ptr::write(out, array.unwrap_or_else(ArrowArray::released)) };
+            0
+        }
+        Err(e) => {
+            private.last_error = CString::new(e.to_string()).unwrap_or_default();
+            EIO
+        }
+    }
+}
+
+unsafe extern "C" fn stream_get_last_error(stream: *mut ArrowArrayStream) -> *const c_char {
+    let private = unsafe { stream_private(stream) };
+    if private.last_error.is_empty() {
+        std::ptr::null()
+    } else {
+        private.last_error.as_ptr()
+    }
+}
+
+unsafe extern "C" fn stream_release(stream: *mut ArrowArrayStream) {
+    let stream = unsafe { &mut *stream };
+    drop(unsafe { Box::from_raw(stream.private_data as *mut PathTibbleStream) });
+    stream.release = None;
+}
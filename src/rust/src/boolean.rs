@@ -0,0 +1,114 @@
+use i_overlay::core::fill_rule::FillRule;
+use i_overlay::core::overlay_rule::OverlayRule;
+use i_overlay::float::simplify::SimplifyShape;
+use i_overlay::float::single::SingleFloatOverlay;
+
+use crate::into_path::{GlyphShapes, Shapes};
+
+/// A boolean operation between the text (subject) and the user-supplied
+/// shapes (clip).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// The parts of the text inside the shapes.
+    Intersection,
+    /// The parts of the text outside the shapes.
+    Difference,
+    /// The parts of the shapes outside the text.
+    InverseDifference,
+    Union,
+    Xor,
+}
+
+impl TryFrom<&str> for BooleanOp {
+    type Error = savvy::Error;
+
+    fn try_from(value: &str) -> savvy::Result<Self> {
+        match value {
+            "intersection" => Ok(Self::Intersection),
+            "difference" => Ok(Self::Difference),
+            "inverse_difference" => Ok(Self::InverseDifference),
+            "union" => Ok(Self::Union),
+            "xor" => Ok(Self::Xor),
+            _ => Err(savvy::Error::new(format!(
+                "Unknown boolean operation: {value}"
+            ))),
+        }
+    }
+}
+
+impl BooleanOp {
+    fn overlay_rule(self) -> OverlayRule {
+        match self {
+            BooleanOp::Intersection => OverlayRule::Intersect,
+            BooleanOp::Difference => OverlayRule::Difference,
+            BooleanOp::InverseDifference => OverlayRule::InverseDifference,
+            BooleanOp::Union => OverlayRule::Union,
+            BooleanOp::Xor => OverlayRule::Xor,
+        }
+    }
+
+    /// Whether the result is a part of the text, so that it can be kept per
+    /// glyph.
+    pub fn is_per_glyph(self) -> bool {
+        matches!(self, BooleanOp::Intersection | BooleanOp::Difference)
+    }
+}
+
+/// Creates the shapes from the points of the contours. The consecutive points
+/// with the same `id` form a contour. Since the direction of the contours is
+/// unknown, the even-odd rule is used to find the holes.
+pub fn shapes_from_points(x: &[f64], y: &[f64], id: &[i32]) -> savvy::Result<Shapes> {
+    if x.len() != y.len() || x.len() != id.len() {
+        return Err(savvy::Error::new(
+            "x, y, and id of the shape must be the same length",
+        ));
+    }
+
+    let mut contours: Vec<Vec<[f32; 2]>> = Vec::new();
+    let mut prev_id = None;
+    for ((&x, &y), &id) in x.iter().zip(y).zip(id) {
+        if !x.is_finite() || !y.is_finite() {
+            return Err(savvy::Error::new("The shape must not contain NA"));
+        }
+        if prev_id != Some(id) {
+            contours.push(Vec::new());
+            prev_id = Some(id);
+        }
+        // `contours` is not empty here.
+        contours.last_mut().unwrap().push([x as f32, y as f32]);
+    }
+
+    // i_overlay needs at least 3 points to form a polygon.
+    contours.retain(|c| c.len() >= 3);
+    Ok(contours.simplify_shape(FillRule::EvenOdd))
+}
+
+/// Applies the boolean operation to the text and the shapes.
+///
+/// If the result is a part of the text (intersection and difference), the
+/// operation is applied to each glyph, keeping the glyph IDs and colors.
+/// Otherwise, all the glyphs are combined into one shape whose glyph ID is 1.
+pub fn apply_boolean(glyph_shapes: GlyphShapes, clip: &Shapes, op: BooleanOp) -> GlyphShapes {
+    let rule = op.overlay_rule();
+
+    if op.is_per_glyph() {
+        return glyph_shapes
+            .into_iter()
+            .filter_map(|(gid, shapes, color)| {
+                let result = shapes.overlay(clip, rule, FillRule::NonZero);
+                (!result.is_empty()).then_some((gid, result, color))
+            })
+            .collect();
+    }
+
+    let text: Shapes = glyph_shapes
+        .into_iter()
+        .flat_map(|(_, shapes, _)| shapes)
+        .collect();
+    let result = text.overlay(clip, rule, FillRule::NonZero);
+    if result.is_empty() {
+        Vec::new()
+    } else {
+        vec![(1, result, None)]
+    }
+}
@@ -1,16 +1,24 @@
+use i_overlay::core::fill_rule::FillRule;
+use i_overlay::float::simplify::SimplifyShape;
+use i_overlay::mesh::outline::offset::OutlineOffset;
+use i_overlay::mesh::style::{LineJoin, OutlineStyle};
 use lyon::{
     geom::euclid::UnknownUnit,
     math::point,
     path::{
         Path,
+        iterator::PathIterator,
         traits::{Build, PathBuilder},
     },
 };
 use skrifa::outline::OutlinePen;
 
+use crate::into_fill_stroke::StrokeStyle;
+use crate::into_path::OutlineFillRule;
+
 // Minimal color type used for COLR glyph layers.
 // Replaces ttf_parser::RgbaColor.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct RgbaColor {
     pub red: u8,
     pub green: u8,
@@ -39,6 +47,10 @@ pub fn color_to_hex(color: Option<RgbaColor>) -> String {
     }
 }
 
+/// Per-glyph outline paths. Each entry holds (glyph_id, path, optional COLR
+/// color).
+pub type GlyphPaths = Vec<(u32, Path, Option<RgbaColor>)>;
+
 pub trait BuildPath: Build<PathType = Path> + PathBuilder {
     // TODO: lyon::path::builder::Transformed is a struct, not a trait. So, this
     // method is needed to forward the operation.
@@ -53,22 +65,48 @@ pub struct LyonPathBuilder<T: BuildPath> {
     pub cur_glyph_id: u32,
 
     // Completed per-glyph paths produced by `finish_glyph()`.
-    // Each entry holds (glyph_id, path, optional COLR color).
-    pub glyph_paths: Vec<(u32, Path, Option<RgbaColor>)>,
+    pub glyph_paths: GlyphPaths,
+
+    // The character of each glyph ID. The character of glyph ID `i` is at `i - 1`.
+    pub glyph_chars: Vec<char>,
 
     // This transformation is of COLR format.
     base_transform: lyon::geom::euclid::Transform2D<f32, UnknownUnit, UnknownUnit>,
 
+    // User-supplied transformation. This is applied after the position is
+    // scaled into the range of [0, 1], but before flattening or tessellation.
+    pub user_transform: lyon::geom::euclid::Transform2D<f32, UnknownUnit, UnknownUnit>,
+
     // multiply by this to scale the position into the range of [0, 1].
     scale_factor: f32,
 
+    // Horizontal skew (tangent of the angle) for synthetic oblique. This is
+    // applied to each glyph around the origin of the glyph.
+    synthetic_skew: f32,
+
+    // Distance (in font design units) to offset the outline outward for
+    // synthetic bold. 0 means no emboldening.
+    pub synthetic_embolden: f32,
+
     offset_x: f32,
     offset_y: f32,
 
     pub tolerance: f32,
 
+    // If true, `tolerance` is relative to the size of each glyph.
+    pub relative_tolerance: bool,
+
+    // Tolerance of flattening for the current glyph.
+    glyph_tolerance: f32,
+
     // line width of the stroke
     pub line_width: f32,
+
+    // line join, caps, and miter limit of the stroke
+    pub stroke_style: StrokeStyle,
+
+    // fill rule of the fill and the path
+    pub fill_rule: OutlineFillRule,
 }
 
 impl<T: BuildPath> LyonPathBuilder<T> {
@@ -78,12 +116,20 @@ impl<T: BuildPath> LyonPathBuilder<T> {
             cur_layer: 0,
             cur_glyph_id: 0,
             glyph_paths: Vec::new(),
+            glyph_chars: Vec::new(),
             base_transform: lyon::geom::euclid::Transform2D::identity(),
+            user_transform: lyon::geom::euclid::Transform2D::identity(),
             scale_factor: 1.,
+            synthetic_skew: 0.,
+            synthetic_embolden: 0.,
             offset_x: 0.,
             offset_y: 0.,
             tolerance,
+            relative_tolerance: false,
+            glyph_tolerance: tolerance,
             line_width,
+            stroke_style: StrokeStyle::default(),
+            fill_rule: OutlineFillRule::default(),
         }
     }
 
@@ -102,9 +148,17 @@ impl<T: BuildPath> LyonPathBuilder<T> {
     pub fn finish_glyph_with_color(&mut self, color: Option<RgbaColor>) {
         let old = std::mem::replace(
             &mut self.builders[self.cur_layer],
-            T::new_builder(self.tolerance),
+            T::new_builder(self.glyph_tolerance),
         );
-        let path = old.build();
+        let mut path = old.build();
+        if self.synthetic_embolden > 0. {
+            // The path is already scaled and transformed by the user transform,
+            // so convert the distance accordingly.
+            let amount = self.synthetic_embolden
+                * self.scale_factor
+                * self.user_transform.determinant().abs().sqrt();
+            path = embolden_path(&path, amount, self.glyph_tolerance, self.fill_rule);
+        }
         if path.iter().next().is_some() {
             self.glyph_paths.push((self.cur_glyph_id, path, color));
         }
@@ -114,11 +168,20 @@ impl<T: BuildPath> LyonPathBuilder<T> {
     pub fn update_transform(&mut self) {
         let transform = self
             .base_transform
+            .then(&lyon::geom::euclid::Transform2D::new(
+                1.,
+                0.,
+                self.synthetic_skew,
+                1.,
+                0.,
+                0.,
+            ))
             .then_translate(lyon::geom::euclid::Vector2D::new(
                 self.offset_x,
                 self.offset_y,
             ))
-            .then_scale(self.scale_factor, self.scale_factor);
+            .then_scale(self.scale_factor, self.scale_factor)
+            .then(&self.user_transform);
         self.cur_builder().set_transform(transform);
     }
 
@@ -164,6 +227,99 @@ impl<T: BuildPath> LyonPathBuilder<T> {
         self.base_transform = transform;
         self.update_transform();
     }
+
+    /// Set the parameters of synthetic bold and oblique. `embolden` is the
+    /// outward offset in font design units, and `skew_degree` is the slant
+    /// angle in degrees.
+    pub fn set_synthetic(&mut self, embolden: f32, skew_degree: f32) {
+        self.synthetic_embolden = embolden;
+        self.synthetic_skew = skew_degree.to_radians().tan();
+        self.update_transform();
+    }
+
+    pub fn set_stroke_style(&mut self, stroke_style: StrokeStyle) {
+        self.stroke_style = stroke_style;
+    }
+
+    pub fn set_relative_tolerance(&mut self, relative_tolerance: bool) {
+        self.relative_tolerance = relative_tolerance;
+    }
+
+    /// Set the tolerance of flattening for the next glyph relative to the size
+    /// of the glyph in font design units. If the size is unknown, the tolerance
+    /// is used as is.
+    pub fn set_glyph_size(&mut self, size: Option<f32>) {
+        self.glyph_tolerance = match size {
+            Some(size) => {
+                self.tolerance
+                    * size
+                    * self.scale_factor
+                    * self.user_transform.determinant().abs().sqrt()
+            }
+            None => self.tolerance,
+        };
+        self.builders[self.cur_layer] = T::new_builder(self.glyph_tolerance);
+        self.update_transform();
+    }
+
+    pub fn set_fill_rule(&mut self, fill_rule: OutlineFillRule) {
+        self.fill_rule = fill_rule;
+    }
+
+    pub fn set_user_transform(
+        &mut self,
+        transform: lyon::geom::euclid::Transform2D<f32, UnknownUnit, UnknownUnit>,
+    ) {
+        self.user_transform = transform;
+        self.update_transform();
+    }
+}
+
+/// Offset the outline of the path outward by `amount` to make it look bolder.
+/// The inside of the outline is determined by `fill_rule`. The result consists
+/// only of line segments.
+pub(crate) fn embolden_path(
+    path: &Path,
+    amount: f32,
+    tolerance: f32,
+    fill_rule: OutlineFillRule,
+) -> Path {
+    let mut contours: Vec<Vec<[f32; 2]>> = Vec::new();
+    for event in path.iter().flattened(tolerance) {
+        match event {
+            lyon::path::Event::Begin { at } => contours.push(vec![[at.x, at.y]]),
+            lyon::path::Event::Line { to, .. } => {
+                if let Some(contour) = contours.last_mut() {
+                    contour.push([to.x, to.y]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Normalize the direction of the contours first; outline() distinguishes
+    // the outer rings and the holes by their direction. The raw contours are
+    // rejected before outlining, as emboldening merges them anyway.
+    let fill_rule = match fill_rule {
+        OutlineFillRule::EvenOdd => FillRule::EvenOdd,
+        OutlineFillRule::NonZero | OutlineFillRule::Raw => FillRule::NonZero,
+    };
+    let shapes = contours.simplify_shape(fill_rule);
+    let style = OutlineStyle::new(amount).line_join(LineJoin::Miter(std::f32::consts::PI / 3.));
+    let emboldened = shapes.outline(&style);
+
+    let mut builder = Path::builder();
+    for contour in emboldened.iter().flatten() {
+        let Some((first, rest)) = contour.split_first() else {
+            continue;
+        };
+        builder.begin(point(first[0], first[1]));
+        for pt in rest {
+            builder.line_to(point(pt[0], pt[1]));
+        }
+        builder.end(true);
+    }
+    builder.build()
 }
 
 // For path
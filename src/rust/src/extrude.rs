@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use lyon::math::point;
+use lyon::path::Path;
+use lyon::tessellation::{
+    BuffersBuilder, FillOptions, FillRule, FillTessellator, FillVertex, VertexBuffers, VertexSource,
+};
+
+use crate::into_fill_stroke::tessellation_error;
+use crate::into_path::{GlyphShapes, Shapes};
+
+/// A triangle mesh in 3D. The triangles are counter-clockwise when seen from
+/// the outside.
+#[derive(Default)]
+pub struct Mesh3D {
+    pub vertices: Vec<[f32; 3]>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl Mesh3D {
+    /// Appends another mesh.
+    pub fn append(&mut self, other: Mesh3D) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.triangles.extend(
+            other
+                .triangles
+                .iter()
+                .map(|t| [t[0] + offset, t[1] + offset, t[2] + offset]),
+        );
+    }
+}
+
+/// A mesh under construction. The vertices at the same position are shared so
+/// that the mesh is closed.
+#[derive(Default)]
+struct MeshBuilder {
+    mesh: Mesh3D,
+    index: HashMap<[u32; 3], u32>,
+}
+
+impl MeshBuilder {
+    fn add_vertex(&mut self, pt: [f32; 2], z: f32) -> u32 {
+        let vertices = &mut self.mesh.vertices;
+        *self
+            .index
+            .entry([pt[0].to_bits(), pt[1].to_bits(), z.to_bits()])
+            .or_insert_with(|| {
+                vertices.push([pt[0], pt[1], z]);
+                vertices.len() as u32 - 1
+            })
+    }
+
+    fn add_triangle(&mut self, triangle: [u32; 3]) {
+        // Skip the degenerated triangles.
+        let [i, j, k] = triangle;
+        if i != j && j != k && k != i {
+            self.mesh.triangles.push(triangle);
+        }
+    }
+}
+
+/// Returns the position of each point of the ring moved by `distance` toward
+/// the inside of the solid. Since the outer rings are counter-clockwise and
+/// the holes are clockwise, the inside is always on the left side.
+fn inset_ring(ring: &[[f32; 2]], distance: f32) -> Vec<[f32; 2]> {
+    // Limit the miter so that sharp corners don't spike.
+    const MITER_LIMIT: f32 = 2.0;
+
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let prev = ring[(i + n - 1) % n];
+            let cur = ring[i];
+            let next = ring[(i + 1) % n];
+
+            let left_normal = |a: [f32; 2], b: [f32; 2]| {
+                let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+                let len = dx.hypot(dy);
+                if len == 0. {
+                    [0., 0.]
+                } else {
+                    [-dy / len, dx / len]
+                }
+            };
+            let n1 = left_normal(prev, cur);
+            let n2 = left_normal(cur, next);
+
+            let (mx, my) = (n1[0] + n2[0], n1[1] + n2[1]);
+            let len = mx.hypot(my);
+            if len == 0. {
+                return cur;
+            }
+
+            // The miter length is 1 / cos(theta / 2), where theta is the angle
+            // between the normals.
+            let cos_half = len / 2.;
+            let scale = (1. / cos_half).min(MITER_LIMIT) * distance;
+            [cur[0] + mx / len * scale, cur[1] + my / len * scale]
+        })
+        .collect()
+}
+
+/// A vertex of the triangulated shapes.
+#[derive(Clone, Copy)]
+struct CapVertex {
+    position: [f32; 2],
+    // The index of the point of the rings at this vertex, counted through all
+    // the rings in order. `None` if the vertex is newly created (e.g. at an
+    // intersection).
+    endpoint: Option<usize>,
+}
+
+/// Triangulates the shapes. The triangles are counter-clockwise.
+pub fn triangulate(
+    shapes: &Shapes,
+    tessellator: &mut FillTessellator,
+    tolerance: f32,
+) -> savvy::Result<VertexBuffers<[f32; 2], u32>> {
+    let geometry = triangulate_with_sources(shapes, tessellator, tolerance)?;
+    Ok(VertexBuffers {
+        vertices: geometry.vertices.iter().map(|v| v.position).collect(),
+        indices: geometry.indices,
+    })
+}
+
+/// Same as `triangulate()`, but keeps which point of the rings each vertex
+/// comes from.
+fn triangulate_with_sources(
+    shapes: &Shapes,
+    tessellator: &mut FillTessellator,
+    tolerance: f32,
+) -> savvy::Result<VertexBuffers<CapVertex, u32>> {
+    // The endpoint IDs of the path, in the order of the points of the rings.
+    let mut endpoints = Vec::new();
+    let mut builder = Path::builder();
+    for ring in shapes.iter().flatten() {
+        let Some((first, rest)) = ring.split_first() else {
+            continue;
+        };
+        endpoints.push(builder.begin(point(first[0], first[1])));
+        for pt in rest {
+            endpoints.push(builder.line_to(point(pt[0], pt[1])));
+        }
+        builder.end(true);
+    }
+    let path = builder.build();
+
+    let mut point_index = vec![None; endpoints.last().map_or(0, |id| id.to_usize() + 1)];
+    for (i, id) in endpoints.iter().enumerate() {
+        point_index[id.to_usize()] = Some(i);
+    }
+
+    let mut geometry: VertexBuffers<CapVertex, u32> = VertexBuffers::new();
+    let options = FillOptions::tolerance(tolerance).with_fill_rule(FillRule::NonZero);
+    tessellator
+        .tessellate_with_ids(
+            path.id_iter(),
+            &path,
+            None,
+            &options,
+            &mut BuffersBuilder::new(&mut geometry, |v: FillVertex| {
+                let endpoint = v.sources().find_map(|source| match source {
+                    VertexSource::Endpoint { id } => {
+                        point_index.get(id.to_usize()).copied().flatten()
+                    }
+                    VertexSource::Edge { .. } => None,
+                });
+                CapVertex {
+                    position: v.position().to_array(),
+                    endpoint,
+                }
+            }),
+        )
+        .map_err(tessellation_error)?;
+
+    // Make sure the triangles are counter-clockwise.
+    for t in geometry.indices.chunks_exact_mut(3) {
+        let [a, b, c] = [t[0], t[1], t[2]].map(|i| geometry.vertices[i as usize].position);
+        let area = (b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1]);
+        if area < 0. {
+            t.swap(1, 2);
+        }
+    }
+
+    Ok(geometry)
+}
+
+/// Extrudes the shapes of a glyph into a closed mesh from `z = 0` to `z =
+/// depth`. If `bevel` is positive, the edges of the front face are chamfered by
+/// the distance.
+fn extrude_shapes(
+    shapes: &Shapes,
+    depth: f32,
+    bevel: f32,
+    tessellator: &mut FillTessellator,
+    tolerance: f32,
+) -> savvy::Result<Mesh3D> {
+    let mut mesh = MeshBuilder::default();
+    let bevel = bevel.clamp(0., depth);
+    let wall_top = depth - bevel;
+
+    // The positions of the front face of each point of the rings, in the same
+    // order as the endpoints of the triangulation; if there's a bevel, the
+    // rings are inset.
+    let mut front_position: Vec<[f32; 2]> = Vec::new();
+
+    for ring in shapes.iter().flatten() {
+        let n = ring.len();
+        if n < 3 {
+            front_position.extend(ring);
+            continue;
+        }
+
+        let inset = if bevel > 0. {
+            inset_ring(ring, bevel)
+        } else {
+            ring.clone()
+        };
+        front_position.extend(&inset);
+
+        // Side walls. The outside is on the right side of the ring.
+        let bottom: Vec<u32> = ring.iter().map(|pt| mesh.add_vertex(*pt, 0.)).collect();
+        let top: Vec<u32> = ring
+            .iter()
+            .map(|pt| mesh.add_vertex(*pt, wall_top))
+            .collect();
+        for i in 0..n {
+            let j = (i + 1) % n;
+            mesh.add_triangle([bottom[i], bottom[j], top[j]]);
+            mesh.add_triangle([bottom[i], top[j], top[i]]);
+        }
+
+        // Bevel
+        if bevel > 0. {
+            let front: Vec<u32> = inset.iter().map(|pt| mesh.add_vertex(*pt, depth)).collect();
+            for i in 0..n {
+                let j = (i + 1) % n;
+                mesh.add_triangle([top[i], top[j], front[j]]);
+                mesh.add_triangle([top[i], front[j], front[i]]);
+            }
+        }
+    }
+
+    // Caps. The back face (z = 0) looks toward -z, so the triangles are
+    // flipped.
+    let geometry = triangulate_with_sources(shapes, tessellator, tolerance)?;
+    let back: Vec<u32> = geometry
+        .vertices
+        .iter()
+        .map(|v| mesh.add_vertex(v.position, 0.))
+        .collect();
+    let front: Vec<u32> = geometry
+        .vertices
+        .iter()
+        .map(|v| {
+            let moved = v
+                .endpoint
+                .and_then(|i| front_position.get(i))
+                .unwrap_or(&v.position);
+            mesh.add_vertex(*moved, depth)
+        })
+        .collect();
+    for t in geometry.indices.chunks_exact(3) {
+        let [i, j, k] = [t[0], t[1], t[2]].map(|i| i as usize);
+        mesh.add_triangle([front[i], front[j], front[k]]);
+        mesh.add_triangle([back[i], back[k], back[j]]);
+    }
+
+    Ok(mesh.mesh)
+}
+
+/// Extrudes the shapes of all the glyphs into one mesh.
+pub fn extrude(
+    glyph_shapes: &GlyphShapes,
+    depth: f32,
+    bevel: f32,
+    tessellator: &mut FillTessellator,
+    tolerance: f32,
+) -> savvy::Result<Mesh3D> {
+    let mut mesh = Mesh3D::default();
+    for (_, shapes, _) in glyph_shapes {
+        mesh.append(extrude_shapes(
+            shapes,
+            depth,
+            bevel,
+            tessellator,
+            tolerance,
+        )?);
+    }
+    Ok(mesh)
+}
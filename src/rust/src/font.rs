@@ -4,7 +4,7 @@ use crate::builder::{BuildPath, LyonPathBuilder, RgbaColor};
 
 use skrifa::color::{Brush, ColorPainter, CompositeMode};
 use skrifa::instance::Location;
-use skrifa::outline::DrawSettings;
+use skrifa::outline::{DrawSettings, OutlineGlyph, OutlinePen};
 use skrifa::prelude::{LocationRef, Size, Tag};
 use skrifa::raw::TableProvider;
 use skrifa::raw::tables::kern::SubtableKind;
@@ -46,14 +46,39 @@ impl From<FontLoadingError> for savvy::Error {
     }
 }
 
-impl<T: BuildPath> LyonPathBuilder<T> {
-    pub fn outline(
-        &mut self,
-        text: &str,
+/// A font face loaded into memory, along with the parameters to render it.
+///
+/// This can be reused across multiple texts so that the font database doesn't
+/// need to be looked up and the font data doesn't need to be loaded every time.
+#[derive(Clone)]
+pub struct LoadedFont {
+    data: fontique::Blob<u8>,
+    index: u32,
+    weight: f32,
+    style: String,
+    // Parameters for LyonPathBuilder::set_synthetic()
+    pub(crate) synthetic_embolden: f32,
+    pub(crate) synthetic_skew: f32,
+}
+
+impl LoadedFont {
+    fn new(data: fontique::Blob<u8>, index: u32, weight: f32, style: &str) -> Self {
+        Self {
+            data,
+            index,
+            weight,
+            style: style.to_string(),
+            synthetic_embolden: 0.,
+            synthetic_skew: 0.,
+        }
+    }
+
+    pub fn from_family(
         font_family: &str,
         font_weight: f64,
         font_style: &str,
-    ) -> savvy::Result<()> {
+        synthetic: bool,
+    ) -> savvy::Result<Self> {
         let weight_value = font_weight as f32;
 
         #[rustfmt::skip]
@@ -88,17 +113,36 @@ impl<T: BuildPath> LyonPathBuilder<T> {
                     other => other,
                 };
 
-                if let Some(fi) = font_info {
-                    if let Some(data) = fi.load(None) {
-                        result = Some((data, fi.index()));
-                    }
+                if let Some(fi) = font_info
+                    && let Some(data) = fi.load(None)
+                {
+                    // When the matched face doesn't have the requested weight
+                    // or style, and no variation axis covers it, fontique
+                    // suggests faux bold and/or skew.
+                    let synthesis = fi.synthesis(
+                        fontique::FontWidth::from_ratio(1.0),
+                        style,
+                        fontique::FontWeight::new(weight_value),
+                    );
+                    result = Some((data, fi.index(), fi.weight().value(), synthesis));
                 }
             }
             result
         };
 
-        if let Some((font_data, index)) = named_result {
-            return self.outline_inner(text, font_data.as_ref(), index, weight_value, font_style);
+        if let Some((font_data, index, face_weight, synthesis)) = named_result {
+            let mut font = Self::new(font_data, index, weight_value, font_style);
+            if synthetic {
+                if synthesis.embolden() {
+                    font.synthetic_embolden = synthetic_embolden_strength(
+                        font.data.as_ref(),
+                        index,
+                        weight_value - face_weight,
+                    );
+                }
+                font.synthetic_skew = synthesis.skew().unwrap_or(0.);
+            }
+            return Ok(font);
         }
 
         savvy::r_eprint!(
@@ -112,20 +156,18 @@ impl<T: BuildPath> LyonPathBuilder<T> {
             let mut collection = FONT_COLLECTION.lock().unwrap();
             let first_name = collection.family_names().next().map(|s| s.to_string());
             let mut result = None;
-            if let Some(name) = first_name {
-                if let Some(family) = collection.family_by_name(&name) {
-                    if let Some(font_info) = family.fonts().first() {
-                        if let Some(data) = font_info.load(None) {
-                            result = Some((data, font_info.index()));
-                        }
-                    }
-                }
+            if let Some(name) = first_name
+                && let Some(family) = collection.family_by_name(&name)
+                && let Some(font_info) = family.fonts().first()
+                && let Some(data) = font_info.load(None)
+            {
+                result = Some((data, font_info.index()));
             }
             result
         };
 
         if let Some((font_data, index)) = fallback_result {
-            return self.outline_inner(text, font_data.as_ref(), index, weight_value, font_style);
+            return Ok(Self::new(font_data, index, weight_value, font_style));
         }
 
         // 3. When no fonts are available, return an error.
@@ -134,32 +176,56 @@ impl<T: BuildPath> LyonPathBuilder<T> {
         Err(FontLoadingError::NoAvailableFonts.into())
     }
 
-    pub fn outline_from_file(&mut self, text: &str, font_file: &str) -> savvy::Result<()> {
+    pub fn from_file(font_file: &str) -> savvy::Result<Self> {
         let font_data_raw =
             std::fs::read(font_file).map_err(|e| savvy::Error::new(e.to_string()))?;
         // Weight/style are unknown for file-loaded fonts; use defaults so variable
         // fonts render at their default design position.
-        self.outline_inner(text, font_data_raw.as_slice(), 0, 400.0, "normal")?;
-        Ok(())
+        let font = Self::new(font_data_raw.into(), 0, 400.0, "normal");
+        // Fail early if the file is not a valid font.
+        font.font_ref()?;
+        Ok(font)
     }
 
-    /// Detects whether `font_data` is a static or variable font and dispatches
-    /// to the appropriate rendering path.
-    fn outline_inner(
+    pub fn font_ref(&self) -> savvy::Result<FontRef<'_>> {
+        FontRef::from_index(self.data.as_ref(), self.index)
+            .map_err(|e| savvy::Error::new(e.to_string()))
+    }
+
+    /// Returns `true` if the font has COLR color glyphs.
+    pub fn has_color_glyphs(&self) -> bool {
+        self.font_ref().is_ok_and(|font| font.colr().is_ok())
+    }
+}
+
+impl<T: BuildPath> LyonPathBuilder<T> {
+    pub fn outline(
         &mut self,
         text: &str,
-        font_data: &[u8],
-        face_index: u32,
-        weight: f32,
-        style: &str,
+        font_family: &str,
+        font_weight: f64,
+        font_style: &str,
+        synthetic: bool,
     ) -> savvy::Result<()> {
-        let font = FontRef::from_index(font_data, face_index)
-            .map_err(|e| savvy::Error::new(e.to_string()))?;
+        let font = LoadedFont::from_family(font_family, font_weight, font_style, synthetic)?;
+        self.outline_font(&font, text)
+    }
+
+    pub fn outline_from_file(&mut self, text: &str, font_file: &str) -> savvy::Result<()> {
+        let font = LoadedFont::from_file(font_file)?;
+        self.outline_font(&font, text)
+    }
 
-        if font.axes().is_empty() {
-            self.outline_static(&font, text)
+    /// Detects whether `font` is a static or variable font and dispatches
+    /// to the appropriate rendering path.
+    pub fn outline_font(&mut self, font: &LoadedFont, text: &str) -> savvy::Result<()> {
+        self.set_synthetic(font.synthetic_embolden, font.synthetic_skew);
+
+        let font_ref = font.font_ref()?;
+        if font_ref.axes().is_empty() {
+            self.outline_static(&font_ref, text)
         } else {
-            self.outline_variable(&font, text, weight, style)
+            self.outline_variable(&font_ref, text, font.weight, &font.style)
         }
     }
 
@@ -213,9 +279,9 @@ impl<T: BuildPath> LyonPathBuilder<T> {
     ) -> savvy::Result<()> {
         let metrics = font.metrics(Size::unscaled(), location);
         // In TrueType, descent is negative, so height = ascent - descent gives total cell height.
-        let height = (metrics.ascent - metrics.descent) as f32;
+        let height = metrics.ascent - metrics.descent;
         self.set_scale_factor(1. / height);
-        let line_height = height + metrics.leading as f32;
+        let line_height = height + metrics.leading;
 
         let outlines = font.outline_glyphs();
         let color_glyphs = font.color_glyphs();
@@ -253,6 +319,7 @@ impl<T: BuildPath> LyonPathBuilder<T> {
 
             // Increment glyph ID for consistency.
             self.cur_glyph_id += 1;
+            self.glyph_chars.push(c);
 
             // Even when we cannot find a glyph, fall back to .notdef (GlyphId 0).
             let cur_glyph = charmap.map(c).unwrap_or(GlyphId::new(0));
@@ -262,6 +329,13 @@ impl<T: BuildPath> LyonPathBuilder<T> {
             }
 
             if !c.is_whitespace() {
+                if self.relative_tolerance {
+                    let size = outlines
+                        .get(cur_glyph)
+                        .and_then(|glyph| glyph_size(&glyph, location));
+                    self.set_glyph_size(size);
+                }
+
                 if let Some(color_glyph) = color_glyphs.get(cur_glyph) {
                     // COLR color glyph: paint produces one finish_glyph_with_color
                     // call per layer via the ColrPainter callbacks.
@@ -283,7 +357,8 @@ impl<T: BuildPath> LyonPathBuilder<T> {
             }
 
             if let Some(advance) = glyph_metrics.advance_width(cur_glyph) {
-                self.add_offset_x(advance);
+                // Synthetic bold makes the glyph wider on both sides.
+                self.add_offset_x(advance + 2. * self.synthetic_embolden);
             }
 
             prev_glyph = Some(cur_glyph);
@@ -293,6 +368,73 @@ impl<T: BuildPath> LyonPathBuilder<T> {
     }
 }
 
+/// Returns the larger of the width and the height (in font design units) of
+/// the glyph, or `None` if the glyph has no outline. The control points are
+/// included, so this can be slightly larger than the actual size.
+fn glyph_size(glyph: &OutlineGlyph<'_>, location: LocationRef<'_>) -> Option<f32> {
+    let mut pen = BoundsPen::default();
+    glyph
+        .draw(DrawSettings::unhinted(Size::unscaled(), location), &mut pen)
+        .ok()?;
+    let [x_min, y_min, x_max, y_max] = pen.bounds?;
+    let size = (x_max - x_min).max(y_max - y_min);
+    (size > 0.).then_some(size)
+}
+
+/// Collects the bounding box of the points of an outline.
+#[derive(Default)]
+struct BoundsPen {
+    bounds: Option<[f32; 4]>,
+}
+
+impl BoundsPen {
+    fn add(&mut self, x: f32, y: f32) {
+        self.bounds = Some(match self.bounds {
+            Some([x_min, y_min, x_max, y_max]) => {
+                [x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y)]
+            }
+            None => [x, y, x, y],
+        });
+    }
+}
+
+impl OutlinePen for BoundsPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.add(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.add(x, y);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.add(cx0, cy0);
+        self.add(x, y);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.add(cx0, cy0);
+        self.add(cx1, cy1);
+        self.add(x, y);
+    }
+
+    fn close(&mut self) {}
+}
+
+/// Returns the outward offset (in font design units) for synthetic bold.
+///
+/// FreeType's `FT_GlyphSlot_Embolden()` expands the outline by 1/48 em on each
+/// side to make a regular face look bold. Here, that amount corresponds to the
+/// weight difference of 300 (e.g. 400 -> 700), and is scaled proportionally.
+fn synthetic_embolden_strength(font_data: &[u8], index: u32, weight_diff: f32) -> f32 {
+    let units_per_em = FontRef::from_index(font_data, index)
+        .ok()
+        .and_then(|font| font.head().ok())
+        .map_or(1000., |head| head.units_per_em() as f32);
+
+    units_per_em / 48. * weight_diff.max(0.) / 300.
+}
+
 /// Returns the kerning adjustment (in font design units) for the given glyph pair.
 /// Iterates `kern` table subtables and returns the first matching horizontal kern value.
 /// Returns 0.0 if no kerning information is available.
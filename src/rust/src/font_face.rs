@@ -0,0 +1,853 @@
+use lyon::tessellation::{FillTessellator, StrokeTessellator};
+use savvy::{
+    ExternalPointerSexp, IntegerSexp, NotAvailableValue, NumericSexp, OwnedRawSexp,
+    OwnedStringSexp, StringSexp, savvy,
+};
+
+use crate::arrow::{ArrowArrayStream, PathTibbleStream};
+use crate::boolean::{BooleanOp, apply_boolean, shapes_from_points};
+use crate::builder::{LyonPathBuilderForPath, LyonPathBuilderForStrokeAndFill};
+use crate::extrude::{Mesh3D, extrude};
+use crate::font::LoadedFont;
+use crate::geojson::write_geojson;
+use crate::geometry::{GeometryFormat, GeometryTibble};
+use crate::into_fill_stroke::StrokeStyle;
+use crate::into_path::{GlyphShapes, GlyphUnion, OutlineFillRule, shapes_to_path, union_glyphs};
+use crate::model::{write_gltf, write_obj, write_stl};
+use crate::offset::{OffsetJoin, offset_shapes, shapes_to_fill};
+use crate::resample::{Resample, Sampling};
+use crate::result::StringsResult;
+use crate::skeleton::{SkeletonOptions, shapes_to_skeleton};
+use crate::svg::write_svg;
+
+/// Converts a numeric vector of `c(a, b, c, d, e, f)`, which represents the
+/// affine transformation
+///
+/// ```text
+/// x' = a * x + c * y + e
+/// y' = b * x + d * y + f
+/// ```
+///
+/// into a transformation matrix. `NULL` means the identity transformation.
+fn to_transform(transform: Option<NumericSexp>) -> savvy::Result<lyon::math::Transform> {
+    let Some(transform) = transform else {
+        return Ok(lyon::math::Transform::identity());
+    };
+
+    let &[a, b, c, d, e, f] = transform.as_slice_f64() else {
+        return Err(savvy::Error::new(
+            "transform must be a numeric vector of length 6",
+        ));
+    };
+
+    Ok(lyon::math::Transform::new(
+        a as _, b as _, c as _, d as _, e as _, f as _,
+    ))
+}
+
+/// Tessellators to be reused across the conversions. In the parallel mode,
+/// each thread has its own ones.
+#[derive(Default)]
+struct Tessellators {
+    fill: FillTessellator,
+    stroke: StrokeTessellator,
+}
+
+/// A string to convert, and the transformation to place it.
+type Job<'a> = (&'a str, lyon::math::Transform);
+
+type ConvertFn<'a, R> =
+    dyn Fn(&str, lyon::math::Transform, &mut Tessellators) -> savvy::Result<R> + Sync + 'a;
+
+/// Pairs each string of `text` with the transformation to place it at the
+/// anchor position `(x[i], y[i])`, which is applied after the user-supplied
+/// transformation.
+fn make_jobs<'a>(
+    text: &'a StringSexp,
+    x: &NumericSexp,
+    y: &NumericSexp,
+    transform: lyon::math::Transform,
+) -> savvy::Result<Vec<Job<'a>>> {
+    let x = x.as_slice_f64();
+    let y = y.as_slice_f64();
+    if x.len() != text.len() || y.len() != text.len() {
+        return Err(savvy::Error::new("x and y must be the same length as text"));
+    }
+
+    if text.iter().any(|s| s.is_na()) {
+        return Err(savvy::Error::new("text must not contain NA"));
+    }
+
+    let jobs = text
+        .iter()
+        .zip(x.iter().zip(y.iter()))
+        .map(|(s, (&x, &y))| {
+            let anchor = lyon::math::vector(x as f32, y as f32);
+            (s, transform.then_translate(anchor))
+        })
+        .collect();
+
+    Ok(jobs)
+}
+
+/// Converts each job by `convert`.
+///
+/// If `threads` is larger than 1, the jobs are split into chunks and converted
+/// concurrently. The number of the threads is at most the number of the CPUs
+/// and the number of the jobs. Note that `convert` must not call any R API
+/// because it might be called outside of the main thread.
+fn convert_jobs<R: Send>(
+    jobs: &[Job<'_>],
+    threads: i32,
+    tessellators: &mut Tessellators,
+    convert: &ConvertFn<'_, R>,
+) -> savvy::Result<Vec<R>> {
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let threads = (threads.max(1) as usize).min(max_threads).min(jobs.len());
+    if threads > 1 {
+        convert_parallel(jobs, threads, convert)
+    } else {
+        jobs.iter()
+            .map(|(s, t)| convert(s, *t, tessellators))
+            .collect()
+    }
+}
+
+/// Splits the jobs into chunks and converts them on multiple threads. The
+/// order of the results is the same as the jobs.
+fn convert_parallel<R: Send>(
+    jobs: &[Job<'_>],
+    threads: usize,
+    convert: &ConvertFn<'_, R>,
+) -> savvy::Result<Vec<R>> {
+    let chunk_size = jobs.len().div_ceil(threads);
+
+    // savvy::Error is not Send, so the error is passed as a string.
+    let chunks: Vec<Result<Vec<R>, String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = jobs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut tessellators = Tessellators::default();
+                    chunk
+                        .iter()
+                        .map(|(s, t)| convert(s, *t, &mut tessellators).map_err(|e| e.to_string()))
+                        .collect()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|_| Err("A worker thread panicked".to_string()))
+            })
+            .collect()
+    });
+
+    let mut results = Vec::with_capacity(jobs.len());
+    for chunk in chunks {
+        results.extend(chunk.map_err(savvy::Error::new)?);
+    }
+    Ok(results)
+}
+
+/// Converts each string of `text` by `convert`, and combines the results into
+/// one.
+fn convert_strings<R>(
+    text: StringSexp,
+    x: NumericSexp,
+    y: NumericSexp,
+    transform: Option<NumericSexp>,
+    threads: i32,
+    tessellators: &mut Tessellators,
+    convert: &ConvertFn<'_, R>,
+) -> savvy::Result<savvy::Sexp>
+where
+    R: StringsResult + Send + TryInto<savvy::Sexp, Error = savvy::Error>,
+{
+    let transform = to_transform(transform)?;
+    let jobs = make_jobs(&text, &x, &y, transform)?;
+
+    if jobs.is_empty() {
+        // Return an empty result with the same columns
+        return convert("", transform, tessellators)?.try_into();
+    }
+
+    let mut results = convert_jobs(&jobs, threads, tessellators, convert)?.into_iter();
+
+    // `jobs` is not empty, so there's at least one result.
+    let mut result = results.next().unwrap();
+    if jobs.len() > 1 {
+        result.init_string_id();
+    }
+    for (i, converted) in results.enumerate() {
+        result.append(converted, i as i32 + 2);
+    }
+
+    result.try_into()
+}
+
+// A font face loaded into memory. This keeps the font data and the
+// tessellators so that the conversions can be repeated without looking up the
+// font database or reloading the font data every time.
+#[savvy]
+pub struct FontFace {
+    font: LoadedFont,
+    tessellators: Tessellators,
+}
+
+#[savvy]
+impl FontFace {
+    fn from_family(
+        font_family: &str,
+        font_weight: f64,
+        font_style: &str,
+        synthetic: bool,
+    ) -> savvy::Result<Self> {
+        let font = LoadedFont::from_family(font_family, font_weight, font_style, synthetic)?;
+        Ok(Self {
+            font,
+            tessellators: Tessellators::default(),
+        })
+    }
+
+    fn from_file(font_file: &str) -> savvy::Result<Self> {
+        let font = LoadedFont::from_file(font_file)?;
+        Ok(Self {
+            font,
+            tessellators: Tessellators::default(),
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn path(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        relative_tolerance: bool,
+        max_segment_length: f64,
+        uniform: bool,
+        fill_rule: &str,
+        union: &str,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let fill_rule = OutlineFillRule::try_from(fill_rule)?;
+        let union = GlyphUnion::try_from(union)?;
+        if fill_rule == OutlineFillRule::Raw && union != GlyphUnion::None {
+            return Err(savvy::Error::new(
+                "The raw contours cannot be merged across glyphs",
+            ));
+        }
+        if fill_rule == OutlineFillRule::Raw && font.synthetic_embolden > 0. {
+            return Err(savvy::Error::new(
+                "The raw contours cannot be emboldened; disable synthetic bold",
+            ));
+        }
+        // 0 means no limit of the segment length.
+        let resample = match (max_segment_length > 0., uniform) {
+            (false, false) => Resample::None,
+            (false, true) => {
+                return Err(savvy::Error::new(
+                    "Uniform resampling requires the maximum segment length",
+                ));
+            }
+            (true, false) => Resample::MaxSegmentLength(max_segment_length as _),
+            (true, true) => Resample::Uniform(max_segment_length as _),
+        };
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, _| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.set_relative_tolerance(relative_tolerance);
+                builder.set_fill_rule(fill_rule);
+                builder.outline_font(font, text)?;
+                builder.into_path_with(union, resample)
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn points(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        n: i32,
+        spacing: f64,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let sampling = match (n > 0, spacing > 0.) {
+            (true, false) => Sampling::Count(n as _),
+            (false, true) => Sampling::Spacing(spacing),
+            _ => {
+                return Err(savvy::Error::new(
+                    "Either the number of points or the spacing must be positive",
+                ));
+            }
+        };
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, _| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                builder.into_points(sampling)
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn skeleton(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        resolution: f64,
+        prune: f64,
+        simplify: f64,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let options = SkeletonOptions {
+            resolution: resolution as _,
+            prune: prune as _,
+            simplify: simplify as _,
+        };
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, _| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                let has_color = builder.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+                shapes_to_skeleton(builder.glyph_shapes(), has_color, options)
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn stroke(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        line_width: NumericSexp,
+        width_profile: NumericSexp,
+        line_join: &str,
+        line_cap: &str,
+        miter_limit: f64,
+        dash: NumericSexp,
+        dash_offset: f64,
+        threads: i32,
+        vertex_attributes: bool,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let stroke_style = StrokeStyle::new(
+            line_join,
+            line_cap,
+            miter_limit,
+            dash.as_slice_f64(),
+            dash_offset,
+            line_width.as_slice_f64(),
+            width_profile.as_slice_f64(),
+        )?;
+        // When the line width varies, this is overwritten for each glyph.
+        let line_width = line_width.as_slice_f64()[0];
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, tessellators| {
+                let mut builder =
+                    LyonPathBuilderForStrokeAndFill::new(tolerance as _, line_width as _);
+                builder.set_user_transform(transform);
+                builder.set_stroke_style(stroke_style.clone());
+                builder.outline_font(font, text)?;
+                builder.into_stroke(&mut tessellators.stroke, vertex_attributes)
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        fill_rule: &str,
+        threads: i32,
+        vertex_attributes: bool,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let fill_rule = OutlineFillRule::try_from(fill_rule)?;
+        if fill_rule == OutlineFillRule::Raw {
+            return Err(savvy::Error::new(
+                "The raw contours cannot be filled; use \"nonzero\" or \"evenodd\"",
+            ));
+        }
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, tessellators| {
+                let mut builder = LyonPathBuilderForStrokeAndFill::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.set_fill_rule(fill_rule);
+                builder.outline_font(font, text)?;
+                builder.into_fill(&mut tessellators.fill, vertex_attributes)
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn offset(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        distance: f64,
+        line_join: &str,
+        fill: bool,
+        union: &str,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let join = OffsetJoin::try_from(line_join)?;
+        let union = GlyphUnion::try_from(union)?;
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, tessellators| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                let has_color = union != GlyphUnion::All
+                    && builder.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+                let shapes =
+                    offset_shapes(builder.glyph_shapes(), distance as _, join, tolerance as _);
+                // The offset shapes of the adjacent glyphs may overlap even if
+                // the glyphs don't.
+                let shapes = union_glyphs(shapes, union);
+                if fill {
+                    shapes_to_fill(shapes, has_color, &mut tessellators.fill, tolerance as _)
+                } else {
+                    Ok(shapes_to_path(shapes, has_color))
+                }
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn boolean(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        shape_x: NumericSexp,
+        shape_y: NumericSexp,
+        shape_id: IntegerSexp,
+        op: &str,
+        fill: bool,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let op = BooleanOp::try_from(op)?;
+        let clip = shapes_from_points(
+            shape_x.as_slice_f64(),
+            shape_y.as_slice_f64(),
+            shape_id.as_slice(),
+        )?;
+
+        // The shape is combined with all the strings at once, not with each
+        // of them; otherwise, e.g. the shape would be repeated for each string.
+        if !op.is_per_glyph() {
+            let transform = to_transform(transform)?;
+            let jobs = make_jobs(&text, &x, &y, transform)?;
+            let glyph_shapes: GlyphShapes = convert_jobs(
+                &jobs,
+                threads,
+                &mut self.tessellators,
+                &|text, transform, _| {
+                    let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                    builder.set_user_transform(transform);
+                    builder.outline_font(font, text)?;
+                    Ok(builder.glyph_shapes())
+                },
+            )?
+            .into_iter()
+            .flatten()
+            .collect();
+
+            let shapes = apply_boolean(glyph_shapes, &clip, op);
+            return if fill {
+                shapes_to_fill(shapes, false, &mut self.tessellators.fill, tolerance as _)?
+                    .try_into()
+            } else {
+                shapes_to_path(shapes, false).try_into()
+            };
+        }
+
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, tessellators| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                let has_color = builder.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+                let shapes = apply_boolean(builder.glyph_shapes(), &clip, op);
+                if fill {
+                    shapes_to_fill(shapes, has_color, &mut tessellators.fill, tolerance as _)
+                } else {
+                    Ok(shapes_to_path(shapes, has_color))
+                }
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn mesh(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        line_width: NumericSexp,
+        width_profile: NumericSexp,
+        line_join: &str,
+        line_cap: &str,
+        miter_limit: f64,
+        dash: NumericSexp,
+        dash_offset: f64,
+        stroke: bool,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let stroke_style = StrokeStyle::new(
+            line_join,
+            line_cap,
+            miter_limit,
+            dash.as_slice_f64(),
+            dash_offset,
+            line_width.as_slice_f64(),
+            width_profile.as_slice_f64(),
+        )?;
+        // When the line width varies, this is overwritten for each glyph.
+        let line_width = line_width.as_slice_f64()[0];
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, tessellators| {
+                let mut builder =
+                    LyonPathBuilderForStrokeAndFill::new(tolerance as _, line_width as _);
+                builder.set_user_transform(transform);
+                builder.set_stroke_style(stroke_style.clone());
+                builder.outline_font(font, text)?;
+                if stroke {
+                    builder.into_stroke_mesh(&mut tessellators.stroke)
+                } else {
+                    builder.into_fill_mesh(&mut tessellators.fill)
+                }
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn curves(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, _| {
+                // Use the non-flattened builder to keep the curves.
+                let mut builder = LyonPathBuilderForStrokeAndFill::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                Ok(builder.into_curves())
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn geometry(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        threads: i32,
+        format: &str,
+        by_string: bool,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let format = GeometryFormat::try_from(format)?;
+        convert_strings(
+            text,
+            x,
+            y,
+            transform,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, _| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                let glyph_shapes = builder.glyph_shapes();
+                if by_string {
+                    Ok(GeometryTibble::by_string(glyph_shapes, format))
+                } else {
+                    Ok(GeometryTibble::by_glyph(glyph_shapes, format))
+                }
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn geojson(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        threads: i32,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let transform = to_transform(transform)?;
+        let jobs = make_jobs(&text, &x, &y, transform)?;
+        let strings = convert_jobs(
+            &jobs,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, _| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                let glyph_shapes = builder.glyph_shapes();
+                Ok((glyph_shapes, builder.glyph_chars))
+            },
+        )?;
+
+        let geojson = write_geojson(&strings);
+        OwnedStringSexp::try_from(geojson)?.into()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn model(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        threads: i32,
+        depth: f64,
+        bevel: f64,
+        format: &str,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let transform = to_transform(transform)?;
+        let jobs = make_jobs(&text, &x, &y, transform)?;
+        let meshes = convert_jobs(
+            &jobs,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, tessellators| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                extrude(
+                    &builder.glyph_shapes(),
+                    depth as _,
+                    bevel as _,
+                    &mut tessellators.fill,
+                    tolerance as _,
+                )
+            },
+        )?;
+
+        let mut mesh = Mesh3D::default();
+        for m in meshes {
+            mesh.append(m);
+        }
+
+        match format {
+            "obj" => OwnedStringSexp::try_from(write_obj(&mesh))?.into(),
+            "stl" => OwnedRawSexp::try_from(write_stl(&mesh).as_slice())?.into(),
+            "gltf" => OwnedStringSexp::try_from(write_gltf(&mesh))?.into(),
+            _ => Err(savvy::Error::new(format!("Unknown model format: {format}"))),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn svg(
+        &mut self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        threads: i32,
+        size: f64,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<savvy::Sexp> {
+        let font = &self.font;
+        let transform = to_transform(transform)?;
+        let jobs = make_jobs(&text, &x, &y, transform)?;
+        let glyph_paths = convert_jobs(
+            &jobs,
+            threads,
+            &mut self.tessellators,
+            &|text, transform, _| {
+                // Use the non-flattened builder to keep the curves.
+                let mut builder = LyonPathBuilderForStrokeAndFill::new(tolerance as _, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(font, text)?;
+                Ok(builder.glyph_paths)
+            },
+        )?;
+
+        let svg = write_svg(&glyph_paths, size as _);
+        OwnedStringSexp::try_from(svg)?.into()
+    }
+
+    /// Writes an Arrow C stream into `stream`, an empty stream allocated by
+    /// `nanoarrow::nanoarrow_allocate_array_stream()`. Each batch is the result
+    /// of each string, which is converted lazily when the consumer requests it.
+    #[allow(clippy::too_many_arguments)]
+    fn arrow_stream(
+        &self,
+        text: StringSexp,
+        x: NumericSexp,
+        y: NumericSexp,
+        tolerance: f64,
+        line_width: f64,
+        kind: &str,
+        stream: savvy::Sexp,
+        transform: Option<NumericSexp>,
+    ) -> savvy::Result<()> {
+        let transform = to_transform(transform)?;
+        let jobs: Vec<(String, lyon::math::Transform)> = make_jobs(&text, &x, &y, transform)?
+            .into_iter()
+            .map(|(s, t)| (s.to_string(), t))
+            .collect();
+
+        let is_nanoarrow_stream = stream
+            .get_class()
+            .is_some_and(|class| class.contains(&"nanoarrow_array_stream"));
+        if !is_nanoarrow_stream {
+            return Err(savvy::Error::new(
+                "stream must be a nanoarrow_array_stream object",
+            ));
+        }
+        let stream = ExternalPointerSexp::try_from(stream)?;
+        // Safety: nanoarrow_array_stream is an external pointer to an
+        // ArrowArrayStream struct.
+        let out = unsafe { stream.cast_mut_unchecked::<ArrowArrayStream>() };
+        if out.is_null() {
+            return Err(savvy::Error::new("stream must not be a null pointer"));
+        }
+        // Overwriting a live stream would leak it.
+        if !unsafe { &*out }.is_released() {
+            return Err(savvy::Error::new("stream must be an empty stream"));
+        }
+
+        // The stream outlives this FontFace, so it needs its own copy of the
+        // font and the tessellators.
+        let font = self.font.clone();
+        let mut tessellators = Tessellators::default();
+        let tolerance = tolerance as f32;
+        let line_width = line_width as f32;
+        let convert: Box<dyn FnMut(&str, lyon::math::Transform) -> _> = match kind {
+            "path" => Box::new(move |text, transform| {
+                let mut builder = LyonPathBuilderForPath::new(tolerance, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(&font, text)?;
+                builder.into_path()
+            }),
+            "stroke" => Box::new(move |text, transform| {
+                let mut builder = LyonPathBuilderForStrokeAndFill::new(tolerance, line_width);
+                builder.set_user_transform(transform);
+                builder.outline_font(&font, text)?;
+                builder.into_stroke(&mut tessellators.stroke, false)
+            }),
+            "fill" => Box::new(move |text, transform| {
+                let mut builder = LyonPathBuilderForStrokeAndFill::new(tolerance, 0.);
+                builder.set_user_transform(transform);
+                builder.outline_font(&font, text)?;
+                builder.into_fill(&mut tessellators.fill, false)
+            }),
+            _ => return Err(savvy::Error::new(format!("Unknown type: {kind}"))),
+        };
+
+        let stream = PathTibbleStream::new(jobs, self.font.has_color_glyphs(), convert)?;
+        unsafe { stream.export(out) };
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt::Write;
+
+use crate::into_path::{GlyphShapes, Shapes};
+
+/// Escapes the string as a JSON string literal.
+fn to_json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str(r#"\""#),
+            '\\' => out.push_str(r"\\"),
+            c if c.is_control() => write!(out, r"\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Converts the shapes into the coordinates of a MultiPolygon.
+fn to_coordinates(shapes: &Shapes) -> String {
+    let polygons: Vec<String> = shapes
+        .iter()
+        .map(|shape| {
+            let rings: Vec<String> = shape
+                .iter()
+                .filter(|c| !c.is_empty())
+                .map(|contour| {
+                    // Repeat the first point to close the ring.
+                    let points: Vec<String> = contour
+                        .iter()
+                        .chain(contour.first())
+                        .map(|pt| format!("[{},{}]", pt[0], pt[1]))
+                        .collect();
+                    format!("[{}]", points.join(","))
+                })
+                .collect();
+            format!("[{}]", rings.join(","))
+        })
+        .collect();
+
+    format!("[{}]", polygons.join(","))
+}
+
+/// Writes the shapes of the strings as a GeoJSON FeatureCollection.
+///
+/// Each glyph (or each layer of a COLR glyph) is a Feature of MultiPolygon,
+/// whose outer rings are counter-clockwise and holes are clockwise as RFC 7946
+/// requires. `strings` is the pairs of the shapes and the characters of each
+/// glyph ID. `glyph_id` is shifted so that it is unique across the strings.
+pub fn write_geojson(strings: &[(GlyphShapes, Vec<char>)]) -> String {
+    let mut features = Vec::new();
+    let mut glyph_offset = 0;
+
+    for (i, (glyph_shapes, glyph_chars)) in strings.iter().enumerate() {
+        for (gid, shapes, color) in glyph_shapes {
+            let character = glyph_chars
+                .get(*gid as usize - 1)
+                .map_or("null".to_string(), |c| to_json_string(&c.to_string()));
+            let color = color.map_or("null".to_string(), |c| to_json_string(&c.to_string()));
+
+            features.push(format!(
+                r#"{{"type":"Feature","properties":{{"string_id":{},"glyph_id":{},"character":{},"color":{}}},"geometry":{{"type":"MultiPolygon","coordinates":{}}}}}"#,
+                i + 1,
+                gid + glyph_offset,
+                character,
+                color,
+                to_coordinates(shapes)
+            ));
+        }
+
+        glyph_offset += glyph_shapes
+            .iter()
+            .map(|(gid, _, _)| *gid)
+            .max()
+            .unwrap_or(0);
+    }
+
+    format!(
+        r#"{{"type":"FeatureCollection","features":[{}]}}"#,
+        features.join(",")
+    )
+}
@@ -0,0 +1,306 @@
+use savvy::{
+    NotAvailableValue, OwnedIntegerSexp, OwnedListSexp, OwnedRawSexp, OwnedRealSexp,
+    OwnedStringSexp,
+};
+
+use crate::builder::color_to_hex;
+use crate::into_path::{GlyphShapes, GlyphUnion, Shapes, union_glyphs};
+use crate::result::StringsResult;
+
+/// The format of the geometry column.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GeometryFormat {
+    /// An sfc object of MULTIPOLYGON.
+    Sfc,
+    /// Well-known text.
+    Wkt,
+    /// Well-known binary (little endian).
+    Wkb,
+}
+
+impl TryFrom<&str> for GeometryFormat {
+    type Error = savvy::Error;
+
+    fn try_from(value: &str) -> savvy::Result<Self> {
+        match value {
+            "sfc" => Ok(Self::Sfc),
+            "wkt" => Ok(Self::Wkt),
+            "wkb" => Ok(Self::Wkb),
+            _ => Err(savvy::Error::new(format!(
+                "Unknown geometry format: {value}"
+            ))),
+        }
+    }
+}
+
+/// An intermediate form of the glyph shapes to convert to a data frame with a
+/// geometry column. Each row is a glyph (or a layer of a COLR glyph), or a
+/// whole string.
+pub struct GeometryTibble {
+    // IDs to distinguish the strings. This field is `None` when the input is a single string.
+    pub string_id: Option<Vec<i32>>,
+    // IDs to distinguish the glyphs. This field is `None` when a row is a whole string.
+    pub glyph_id: Option<Vec<i32>>,
+    // Color of color emoji font.
+    pub color: Option<Vec<String>>,
+    // Shapes of each row, which is converted to a MULTIPOLYGON.
+    pub geometry: Vec<Shapes>,
+    pub format: GeometryFormat,
+}
+
+impl GeometryTibble {
+    /// Creates a row per glyph.
+    pub fn by_glyph(glyph_shapes: GlyphShapes, format: GeometryFormat) -> Self {
+        let has_color = glyph_shapes.iter().any(|(_, _, c)| c.is_some());
+
+        let mut glyph_id = Vec::with_capacity(glyph_shapes.len());
+        let mut color = Vec::new();
+        let mut geometry = Vec::with_capacity(glyph_shapes.len());
+        for (gid, shapes, paint_color) in glyph_shapes {
+            glyph_id.push(gid as i32);
+            if has_color {
+                color.push(color_to_hex(paint_color));
+            }
+            geometry.push(shapes);
+        }
+
+        Self {
+            string_id: None,
+            glyph_id: Some(glyph_id),
+            color: if has_color { Some(color) } else { None },
+            geometry,
+            format,
+        }
+    }
+
+    /// Creates a single row of all the glyphs. The glyphs are merged so that
+    /// the polygons don't overlap even when the glyphs do, as a valid
+    /// MULTIPOLYGON requires. The colors of COLR glyphs are discarded.
+    pub fn by_string(glyph_shapes: GlyphShapes, format: GeometryFormat) -> Self {
+        let shapes = union_glyphs(glyph_shapes, GlyphUnion::All)
+            .into_iter()
+            .flat_map(|(_, shapes, _)| shapes)
+            .collect();
+
+        Self {
+            string_id: None,
+            glyph_id: None,
+            color: None,
+            geometry: vec![shapes],
+            format,
+        }
+    }
+}
+
+impl StringsResult for GeometryTibble {
+    fn init_string_id(&mut self) {
+        self.string_id = Some(vec![1; self.geometry.len()]);
+    }
+
+    /// `glyph_id` is shifted so that it is unique across the whole result.
+    fn append(&mut self, other: Self, string_id: i32) {
+        let n = other.geometry.len();
+        let n_self = self.geometry.len();
+
+        if let (Some(dst), Some(src)) = (&mut self.glyph_id, other.glyph_id) {
+            let offset = dst.iter().max().copied().unwrap_or(0);
+            dst.extend(src.iter().map(|i| i + offset));
+        }
+
+        // Non-COLR glyphs in mixed text default to opaque black.
+        match (&mut self.color, other.color) {
+            (Some(dst), Some(src)) => dst.extend(src),
+            (Some(dst), None) => dst.extend(std::iter::repeat_n(color_to_hex(None), n)),
+            (None, Some(src)) => {
+                let mut color: Vec<String> =
+                    std::iter::repeat_n(color_to_hex(None), n_self).collect();
+                color.extend(src);
+                self.color = Some(color);
+            }
+            (None, None) => {}
+        }
+
+        if let Some(dst) = &mut self.string_id {
+            dst.extend(std::iter::repeat_n(string_id, n));
+        }
+
+        self.geometry.extend(other.geometry);
+    }
+}
+
+/// Converts a contour into a 2-column matrix of a closed ring.
+fn ring_to_matrix(contour: &[[f32; 2]]) -> savvy::Result<OwnedRealSexp> {
+    let n = contour.len() + 1;
+    let mut out = OwnedRealSexp::new(n * 2)?;
+
+    // Repeat the first point to close the ring.
+    for (i, pt) in contour.iter().chain(contour.first()).enumerate() {
+        out.set_elt(i, pt[0] as f64)?;
+        out.set_elt(n + i, pt[1] as f64)?;
+    }
+
+    out.set_dim(&[n, 2])?;
+    Ok(out)
+}
+
+/// Converts the shapes into an sfg object of MULTIPOLYGON.
+fn shapes_to_sfg(shapes: &Shapes) -> savvy::Result<OwnedListSexp> {
+    let mut polygons = OwnedListSexp::new(shapes.len(), false)?;
+    for (i, shape) in shapes.iter().enumerate() {
+        let contours: Vec<_> = shape.iter().filter(|c| !c.is_empty()).collect();
+        let mut rings = OwnedListSexp::new(contours.len(), false)?;
+        for (j, contour) in contours.into_iter().enumerate() {
+            rings.set_value(j, ring_to_matrix(contour)?)?;
+        }
+        polygons.set_value(i, rings)?;
+    }
+    polygons.set_class(["XY", "MULTIPOLYGON", "sfg"])?;
+    Ok(polygons)
+}
+
+/// Converts the list of shapes into an sfc object of MULTIPOLYGON. The
+/// attributes are the same as what `sf::st_sfc()` sets when CRS is missing.
+fn to_sfc(geometry: &[Shapes]) -> savvy::Result<OwnedListSexp> {
+    let mut out = OwnedListSexp::new(geometry.len(), false)?;
+    for (i, shapes) in geometry.iter().enumerate() {
+        out.set_value(i, shapes_to_sfg(shapes)?)?;
+    }
+
+    let points = geometry.iter().flatten().flatten().flatten();
+    let bbox = points.fold(None, |acc: Option<[f64; 4]>, pt| {
+        let (x, y) = (pt[0] as f64, pt[1] as f64);
+        let [xmin, ymin, xmax, ymax] = acc.unwrap_or([x, y, x, y]);
+        Some([xmin.min(x), ymin.min(y), xmax.max(x), ymax.max(y)])
+    });
+    let mut bbox = OwnedRealSexp::try_from(bbox.unwrap_or([f64::na(); 4]).as_slice())?;
+    bbox.set_names(["xmin", "ymin", "xmax", "ymax"])?;
+    bbox.set_class(["bbox"])?;
+
+    let mut crs = OwnedListSexp::new(2, true)?;
+    crs.set_name_and_value(0, "input", OwnedStringSexp::try_from_scalar(<&str>::na())?)?;
+    crs.set_name_and_value(1, "wkt", OwnedStringSexp::try_from_scalar(<&str>::na())?)?;
+    crs.set_class(["crs"])?;
+
+    out.set_attrib("precision", OwnedRealSexp::try_from_scalar(0.0)?.into())?;
+    out.set_attrib("bbox", bbox.into())?;
+    out.set_attrib("crs", crs.into())?;
+    let n_empty = geometry.iter().filter(|shapes| shapes.is_empty()).count();
+    out.set_attrib(
+        "n_empty",
+        OwnedIntegerSexp::try_from_scalar(n_empty as i32)?.into(),
+    )?;
+    out.set_class(["sfc_MULTIPOLYGON", "sfc"])?;
+
+    Ok(out)
+}
+
+/// Converts the shapes into the WKT of MULTIPOLYGON.
+fn to_wkt(shapes: &Shapes) -> String {
+    if shapes.is_empty() {
+        return "MULTIPOLYGON EMPTY".to_string();
+    }
+
+    let polygons: Vec<String> = shapes
+        .iter()
+        .map(|shape| {
+            let rings: Vec<String> = shape
+                .iter()
+                .filter(|c| !c.is_empty())
+                .map(|contour| {
+                    // Repeat the first point to close the ring.
+                    let points: Vec<String> = contour
+                        .iter()
+                        .chain(contour.first())
+                        .map(|pt| format!("{} {}", pt[0], pt[1]))
+                        .collect();
+                    format!("({})", points.join(", "))
+                })
+                .collect();
+            format!("({})", rings.join(", "))
+        })
+        .collect();
+
+    format!("MULTIPOLYGON ({})", polygons.join(", "))
+}
+
+/// Converts the shapes into the WKB of MULTIPOLYGON in little endian.
+fn to_wkb(shapes: &Shapes) -> Vec<u8> {
+    const WKB_POLYGON: u32 = 3;
+    const WKB_MULTIPOLYGON: u32 = 6;
+    const LITTLE_ENDIAN: u8 = 1;
+
+    let mut out = vec![LITTLE_ENDIAN];
+    out.extend(WKB_MULTIPOLYGON.to_le_bytes());
+    out.extend((shapes.len() as u32).to_le_bytes());
+
+    for shape in shapes {
+        let contours: Vec<_> = shape.iter().filter(|c| !c.is_empty()).collect();
+
+        out.push(LITTLE_ENDIAN);
+        out.extend(WKB_POLYGON.to_le_bytes());
+        out.extend((contours.len() as u32).to_le_bytes());
+
+        for contour in contours {
+            out.extend((contour.len() as u32 + 1).to_le_bytes());
+            // Repeat the first point to close the ring.
+            for pt in contour.iter().chain(contour.first()) {
+                out.extend((pt[0] as f64).to_le_bytes());
+                out.extend((pt[1] as f64).to_le_bytes());
+            }
+        }
+    }
+
+    out
+}
+
+impl TryFrom<GeometryTibble> for savvy::Sexp {
+    type Error = savvy::Error;
+
+    fn try_from(value: GeometryTibble) -> savvy::Result<Self> {
+        let len = 1
+            + value.string_id.is_some() as usize
+            + value.glyph_id.is_some() as usize
+            + value.color.is_some() as usize;
+        let mut out = OwnedListSexp::new(len, true)?;
+
+        let mut idx = 0;
+
+        if let Some(string_id) = value.string_id {
+            let v = <OwnedIntegerSexp>::try_from(string_id.as_slice())?;
+            out.set_name_and_value(idx, "string_id", v)?;
+            idx += 1;
+        }
+
+        if let Some(glyph_id) = value.glyph_id {
+            let v = <OwnedIntegerSexp>::try_from(glyph_id.as_slice())?;
+            out.set_name_and_value(idx, "glyph_id", v)?;
+            idx += 1;
+        }
+
+        if let Some(color) = value.color {
+            let v = <OwnedStringSexp>::try_from(color.as_slice())?;
+            out.set_name_and_value(idx, "color", v)?;
+            idx += 1;
+        }
+
+        match value.format {
+            GeometryFormat::Sfc => {
+                out.set_name_and_value(idx, "geometry", to_sfc(&value.geometry)?)?;
+            }
+            GeometryFormat::Wkt => {
+                let wkt: Vec<String> = value.geometry.iter().map(to_wkt).collect();
+                let v = <OwnedStringSexp>::try_from(wkt.as_slice())?;
+                out.set_name_and_value(idx, "geometry", v)?;
+            }
+            GeometryFormat::Wkb => {
+                let mut v = OwnedListSexp::new(value.geometry.len(), false)?;
+                for (i, shapes) in value.geometry.iter().enumerate() {
+                    v.set_value(i, OwnedRawSexp::try_from(to_wkb(shapes).as_slice())?)?;
+                }
+                out.set_name_and_value(idx, "geometry", v)?;
+            }
+        }
+
+        out.into()
+    }
+}
@@ -0,0 +1,73 @@
+use lyon::path::Event;
+
+use crate::builder::{LyonPathBuilderForStrokeAndFill, color_to_hex};
+use crate::result::{CurveColumns, PathTibble};
+
+impl LyonPathBuilderForStrokeAndFill {
+    /// Convert the outline paths into the path events without flattening.
+    ///
+    /// Each row is a path event (move, line, quad, cubic, or close). `x` and
+    /// `y` are the end point of the segment, and the control points are NA
+    /// when the verb doesn't have them.
+    pub fn into_curves(self) -> PathTibble {
+        let has_color = self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+
+        let mut x = Vec::new();
+        let mut y = Vec::new();
+        let mut glyph_id = Vec::new();
+        let mut path_id = Vec::new();
+        let mut curve = CurveColumns::default();
+        let mut color_vec: Vec<String> = Vec::new();
+        let mut out_path_id: i32 = 0;
+
+        for (gid, glyph_path, paint_color) in &self.glyph_paths {
+            for event in glyph_path.iter() {
+                let (verb, to, ctrl1, ctrl2) = match event {
+                    Event::Begin { at } => {
+                        out_path_id += 1;
+                        ("move", at, None, None)
+                    }
+                    Event::Line { to, .. } => ("line", to, None, None),
+                    Event::Quadratic { ctrl, to, .. } => ("quad", to, Some(ctrl), None),
+                    Event::Cubic {
+                        ctrl1, ctrl2, to, ..
+                    } => ("cubic", to, Some(ctrl1), Some(ctrl2)),
+                    // The outlines of glyphs are always closed, but just in case.
+                    Event::End { close: false, .. } => continue,
+                    Event::End { first, .. } => ("close", first, None, None),
+                };
+
+                x.push(to.x as f64);
+                y.push(to.y as f64);
+                glyph_id.push(*gid as i32);
+                path_id.push(out_path_id);
+
+                curve.verb.push(verb);
+                curve.ctrl1_x.push(ctrl1.map(|p| p.x as f64));
+                curve.ctrl1_y.push(ctrl1.map(|p| p.y as f64));
+                curve.ctrl2_x.push(ctrl2.map(|p| p.x as f64));
+                curve.ctrl2_y.push(ctrl2.map(|p| p.y as f64));
+
+                if has_color {
+                    color_vec.push(color_to_hex(*paint_color));
+                }
+            }
+        }
+
+        PathTibble {
+            x,
+            y,
+            string_id: None,
+            glyph_id,
+            path_id: Some(path_id),
+            shape_id: None,
+            is_hole: None,
+            distance: None,
+            curve: Some(curve),
+            triangle_id: None,
+            stroke_attributes: None,
+            uv: None,
+            color: if has_color { Some(color_vec) } else { None },
+        }
+    }
+}
@@ -1,84 +1,616 @@
 use crate::{
     builder::{LyonPathBuilderForStrokeAndFill, RgbaColor, color_to_hex},
-    result::PathTibble,
+    result::{MeshTibble, PathTibble, StrokeColumns, UvColumns},
 };
 
+use std::collections::HashMap;
+
+use lyon::algorithms::aabb::bounding_box;
+use lyon::algorithms::measure::{PathMeasurements, SampleType};
+use lyon::path::iterator::PathIterator;
+use lyon::path::{AttributeStore, Event, Path, PathEvent};
 use lyon::tessellation::*;
 
 #[derive(Copy, Clone, Debug)]
-struct Vertex(lyon::math::Point);
+struct Vertex {
+    position: lyon::math::Point,
+    // The following fields are meaningful only for strokes.
+    normal: lyon::math::Vector,
+    advancement: f32,
+    side: Side,
+}
 
 struct VertexCtor {}
 
 impl FillVertexConstructor<Vertex> for VertexCtor {
     fn new_vertex(&mut self, vertex: FillVertex) -> Vertex {
-        Vertex(vertex.position())
+        Vertex {
+            position: vertex.position(),
+            normal: lyon::math::vector(0., 0.),
+            advancement: 0.,
+            side: Side::Positive,
+        }
     }
 }
 
 impl StrokeVertexConstructor<Vertex> for VertexCtor {
     fn new_vertex(&mut self, vertex: StrokeVertex) -> Vertex {
-        Vertex(vertex.position())
+        Vertex {
+            position: vertex.position(),
+            normal: vertex.normal(),
+            advancement: vertex.advancement(),
+            side: vertex.side(),
+        }
+    }
+}
+
+/// The maximum number of the dashes and the gaps in a contour, to avoid
+/// exhausting the memory with a pattern that is too short.
+const MAX_DASHES_PER_CONTOUR: usize = 1_000_000;
+
+/// A dash pattern of the stroke.
+#[derive(Clone, Debug)]
+pub struct DashPattern {
+    // The lengths of the dashes and the gaps, alternately. The length is
+    // always even.
+    lengths: Vec<f32>,
+    // The distance into the pattern at the start of each contour.
+    offset: f32,
+}
+
+impl DashPattern {
+    /// Returns `None` if `lengths` is empty, which means a solid line.
+    pub fn new(lengths: &[f64], offset: f64) -> savvy::Result<Option<Self>> {
+        if lengths.is_empty() {
+            return Ok(None);
+        }
+        if lengths.iter().any(|l| !l.is_finite() || *l < 0.) {
+            return Err(savvy::Error::new("dash must be non-negative numbers"));
+        }
+        if lengths.iter().sum::<f64>() <= 0. {
+            return Err(savvy::Error::new("dash must contain a positive length"));
+        }
+        if !offset.is_finite() {
+            return Err(savvy::Error::new("dash_offset must be a finite number"));
+        }
+
+        let mut lengths: Vec<f32> = lengths.iter().map(|l| *l as f32).collect();
+        if !lengths.iter().sum::<f32>().is_finite() {
+            return Err(savvy::Error::new("dash is too large"));
+        }
+        // An odd number of lengths is repeated to make it even, as SVG does.
+        if lengths.len() % 2 == 1 {
+            lengths.extend_from_within(..);
+        }
+
+        Ok(Some(Self {
+            lengths,
+            offset: offset as _,
+        }))
+    }
+
+    /// Splits each contour of the path into dashes. The pattern restarts at
+    /// the start of each contour. The custom attributes of the path, if any,
+    /// are interpolated into the dashes.
+    ///
+    /// Returns an error if the pattern is too short for the tolerance, or if
+    /// a contour would be split into too many dashes.
+    fn apply(&self, path: &Path, tolerance: f32) -> savvy::Result<Path> {
+        let period: f32 = self.lengths.iter().sum();
+        // A period that is not longer than the tolerance produces dashes
+        // smaller than the precision of the path (or none at all, if the
+        // lengths underflow to zero in f32).
+        if period <= tolerance {
+            return Err(savvy::Error::new(
+                "The total length of dash must be larger than tolerance",
+            ));
+        }
+
+        // The positions are calculated as `k * period + prefix[i]` in f64
+        // rather than by accumulating the lengths, which never reaches the
+        // end of the contour when the lengths are tiny compared to it.
+        let period = period as f64;
+        let prefix: Vec<f64> = self
+            .lengths
+            .iter()
+            .scan(0., |acc, l| {
+                let start = *acc;
+                *acc += *l as f64;
+                Some(start)
+            })
+            .collect();
+        let offset = (self.offset as f64).rem_euclid(period);
+
+        let mut builder = Path::builder_with_attributes(path.num_attributes());
+
+        for contour in split_contours(path) {
+            let measurements = PathMeasurements::from_path(&contour, tolerance);
+            let mut sampler = measurements.create_sampler_with_attributes(
+                &contour,
+                &contour,
+                SampleType::Distance,
+            );
+            let length = sampler.length() as f64;
+
+            let n_periods = ((length + offset) / period).ceil();
+            if n_periods * self.lengths.len() as f64 > MAX_DASHES_PER_CONTOUR as f64 {
+                return Err(savvy::Error::new(format!(
+                    "dash is too short; a contour would be split into more than {MAX_DASHES_PER_CONTOUR} dashes"
+                )));
+            }
+
+            for k in 0..n_periods as usize {
+                let base = k as f64 * period - offset;
+                // Even ones are dashes, and odd ones are gaps.
+                for (i, dash_length) in self.lengths.iter().enumerate().step_by(2) {
+                    let start = base + prefix[i];
+                    let end = start + *dash_length as f64;
+                    if start >= length {
+                        break;
+                    }
+                    if end > 0. {
+                        sampler.split_range(
+                            start.max(0.) as f32..end.min(length) as f32,
+                            &mut builder,
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// Splits the path into the paths of each contour, keeping the custom
+/// attributes.
+fn split_contours(path: &Path) -> Vec<Path> {
+    let num_attributes = path.num_attributes();
+    let mut contours = Vec::new();
+    let mut builder = Path::builder_with_attributes(num_attributes);
+    for event in path.iter_with_attributes() {
+        match event {
+            Event::Begin { at: (at, attr) } => {
+                builder.begin(at, attr);
+            }
+            Event::Line { to: (to, attr), .. } => {
+                builder.line_to(to, attr);
+            }
+            Event::Quadratic {
+                ctrl,
+                to: (to, attr),
+                ..
+            } => {
+                builder.quadratic_bezier_to(ctrl, to, attr);
+            }
+            Event::Cubic {
+                ctrl1,
+                ctrl2,
+                to: (to, attr),
+                ..
+            } => {
+                builder.cubic_bezier_to(ctrl1, ctrl2, to, attr);
+            }
+            Event::End { close, .. } => {
+                builder.end(close);
+                let next = Path::builder_with_attributes(num_attributes);
+                contours.push(std::mem::replace(&mut builder, next).build());
+            }
+        }
+    }
+    contours
+}
+
+/// The line width that varies across the glyphs and along the contours.
+#[derive(Clone, Debug)]
+pub struct VariableWidth {
+    // The line width of each glyph, recycled by the glyph ID.
+    glyph_widths: Vec<f32>,
+    // The multipliers of the line width at the evenly spaced positions along
+    // each contour, from the start to the end. Empty means 1.
+    profile: Vec<f32>,
+}
+
+impl VariableWidth {
+    /// Returns `None` if the line width is constant.
+    pub fn new(glyph_widths: &[f64], profile: &[f64]) -> savvy::Result<Option<Self>> {
+        if glyph_widths.is_empty() {
+            return Err(savvy::Error::new("line_width must not be empty"));
+        }
+        if glyph_widths
+            .iter()
+            .chain(profile)
+            .any(|w| !w.is_finite() || *w < 0.)
+        {
+            return Err(savvy::Error::new("line width must be non-negative numbers"));
+        }
+        if glyph_widths.len() == 1 && profile.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            glyph_widths: glyph_widths.iter().map(|w| *w as f32).collect(),
+            profile: profile.iter().map(|w| *w as f32).collect(),
+        }))
+    }
+
+    fn glyph_width(&self, glyph_id: u32) -> f32 {
+        // glyph_id is 1-based
+        self.glyph_widths[(glyph_id as usize - 1) % self.glyph_widths.len()]
+    }
+
+    /// Linearly interpolates the profile at `t`, the position along the
+    /// contour normalized to [0, 1].
+    fn multiplier(&self, t: f32) -> f32 {
+        match self.profile.as_slice() {
+            [] => 1.,
+            [w] => *w,
+            profile => {
+                let pos = t.clamp(0., 1.) * (profile.len() - 1) as f32;
+                let i = (pos.floor() as usize).min(profile.len() - 2);
+                let frac = pos - i as f32;
+                profile[i] * (1. - frac) + profile[i + 1] * frac
+            }
+        }
+    }
+
+    /// Returns the path with the multiplier of the line width as the custom
+    /// attribute of each endpoint. A closed contour is explicitly closed with
+    /// a line so that the closing point gets the width of the end.
+    fn apply(&self, path: &Path, tolerance: f32) -> Path {
+        let mut builder = Path::builder_with_attributes(1);
+
+        for contour in split_contours(path) {
+            let lengths: Vec<f32> = contour
+                .iter()
+                .filter_map(|event| segment_length(&event, tolerance))
+                .collect();
+            let total: f32 = lengths.iter().sum();
+            let mut lengths = lengths.into_iter();
+            let mut advancement = 0.;
+            let mut next_multiplier = |advancement: &mut f32| {
+                *advancement += lengths.next().unwrap_or(0.);
+                let t = if total > 0. { *advancement / total } else { 0. };
+                [self.multiplier(t)]
+            };
+
+            for event in contour.iter() {
+                match event {
+                    PathEvent::Begin { at } => {
+                        builder.begin(at, &[self.multiplier(0.)]);
+                    }
+                    PathEvent::Line { to, .. } => {
+                        builder.line_to(to, &next_multiplier(&mut advancement));
+                    }
+                    PathEvent::Quadratic { ctrl, to, .. } => {
+                        builder.quadratic_bezier_to(ctrl, to, &next_multiplier(&mut advancement));
+                    }
+                    PathEvent::Cubic {
+                        ctrl1, ctrl2, to, ..
+                    } => {
+                        builder.cubic_bezier_to(
+                            ctrl1,
+                            ctrl2,
+                            to,
+                            &next_multiplier(&mut advancement),
+                        );
+                    }
+                    PathEvent::End { last, first, close } => {
+                        if close && last != first {
+                            builder.line_to(first, &next_multiplier(&mut advancement));
+                        }
+                        builder.end(close);
+                    }
+                }
+            }
+        }
+
+        builder.build()
+    }
+}
+
+/// Returns the length of the segment. The closing segment of a closed contour
+/// is counted, and the other events are `None`.
+fn segment_length(event: &PathEvent, tolerance: f32) -> Option<f32> {
+    match *event {
+        PathEvent::Begin { .. } => None,
+        PathEvent::Line { from, to } => Some((to - from).length()),
+        PathEvent::Quadratic { from, ctrl, to } => {
+            Some(lyon::geom::QuadraticBezierSegment { from, ctrl, to }.length())
+        }
+        PathEvent::Cubic {
+            from,
+            ctrl1,
+            ctrl2,
+            to,
+        } => Some(
+            lyon::geom::CubicBezierSegment {
+                from,
+                ctrl1,
+                ctrl2,
+                to,
+            }
+            .approximate_length(tolerance),
+        ),
+        PathEvent::End { last, first, close } => {
+            (close && last != first).then(|| (first - last).length())
+        }
     }
 }
 
+/// The styles of the stroke other than the line width.
+#[derive(Clone, Debug)]
+pub struct StrokeStyle {
+    pub line_join: LineJoin,
+    // Caps apply only to open paths. Since the outlines of glyphs are closed,
+    // these are effective only when the paths are split (e.g. dashes).
+    pub start_cap: LineCap,
+    pub end_cap: LineCap,
+    pub miter_limit: f32,
+    // `None` means a solid line.
+    pub dash: Option<DashPattern>,
+    // `None` means the line width of the builder is used.
+    pub variable_width: Option<VariableWidth>,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            line_join: StrokeOptions::DEFAULT_LINE_JOIN,
+            start_cap: StrokeOptions::DEFAULT_LINE_CAP,
+            end_cap: StrokeOptions::DEFAULT_LINE_CAP,
+            miter_limit: StrokeOptions::DEFAULT_MITER_LIMIT,
+            dash: None,
+            variable_width: None,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(
+        line_join: &str,
+        line_cap: &str,
+        miter_limit: f64,
+        dash: &[f64],
+        dash_offset: f64,
+        line_width: &[f64],
+        width_profile: &[f64],
+    ) -> savvy::Result<Self> {
+        let line_join = match line_join {
+            "miter" => LineJoin::Miter,
+            "miter_clip" => LineJoin::MiterClip,
+            "round" => LineJoin::Round,
+            "bevel" => LineJoin::Bevel,
+            _ => {
+                return Err(savvy::Error::new(format!("Unknown line join: {line_join}")));
+            }
+        };
+        let line_cap = match line_cap {
+            "butt" => LineCap::Butt,
+            "square" => LineCap::Square,
+            "round" => LineCap::Round,
+            _ => return Err(savvy::Error::new(format!("Unknown line cap: {line_cap}"))),
+        };
+        // lyon panics if the miter limit is smaller than 1.
+        if miter_limit.is_nan() || miter_limit < StrokeOptions::MINIMUM_MITER_LIMIT as f64 {
+            return Err(savvy::Error::new("miter_limit must be 1 or larger"));
+        }
+
+        Ok(Self {
+            line_join,
+            start_cap: line_cap,
+            end_cap: line_cap,
+            miter_limit: miter_limit as _,
+            dash: DashPattern::new(dash, dash_offset)?,
+            variable_width: VariableWidth::new(line_width, width_profile)?,
+        })
+    }
+}
+
+/// The frame of the texture coordinates of a glyph.
+struct UvFrame {
+    // The transform from the output coordinates back to the coordinates
+    // before the user transform, so that the texture follows the glyph.
+    to_glyph: lyon::math::Transform,
+    // The bounding box of all the layers of the glyph in the coordinates
+    // before the user transform.
+    bbox: lyon::math::Box2D,
+}
+
+impl UvFrame {
+    fn uv(&self, position: lyon::math::Point) -> (f64, f64) {
+        let p = self.to_glyph.transform_point(position);
+        let normalize = |value: f32, min: f32, max: f32| {
+            if max > min {
+                ((value - min) / (max - min)) as f64
+            } else {
+                0.
+            }
+        };
+        (
+            normalize(p.x, self.bbox.min.x, self.bbox.max.x),
+            normalize(p.y, self.bbox.min.y, self.bbox.max.y),
+        )
+    }
+}
+
+/// Converts the error of lyon's tessellators so that it's returned to R
+/// instead of panicking.
+pub(crate) fn tessellation_error(e: TessellationError) -> savvy::Error {
+    savvy::Error::new(format!("Failed to tessellate the outlines: {e}"))
+}
+
+/// Tessellated triangles of a glyph. Each entry holds (glyph_id, vertex
+/// buffers, optional COLR color).
+type GlyphBuffers = Vec<(i32, VertexBuffers<Vertex, usize>, Option<RgbaColor>)>;
+
 impl LyonPathBuilderForStrokeAndFill {
-    /// Convert the outline paths into fill as triangles.
-    pub fn into_fill(self) -> PathTibble {
+    fn empty_triangles(&self, stroke_attributes: bool, uv: bool) -> PathTibble {
         let has_color = self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
-        let mut result = PathTibble {
+        PathTibble {
             x: Vec::new(),
             y: Vec::new(),
+            string_id: None,
             glyph_id: Vec::new(),
             path_id: None,
+            shape_id: None,
+            is_hole: None,
+            distance: None,
+            curve: None,
             triangle_id: Some(Vec::new()),
+            stroke_attributes: stroke_attributes.then(StrokeColumns::default),
+            uv: uv.then(UvColumns::default),
             color: if has_color { Some(Vec::new()) } else { None },
-        };
+        }
+    }
 
-        let mut tessellator = FillTessellator::new();
-        let options = FillOptions::tolerance(self.tolerance).with_fill_rule(FillRule::NonZero);
+    /// Returns the frame of the texture coordinates of each glyph. The layers
+    /// of a COLR glyph share the same frame.
+    fn uv_frames(&self) -> HashMap<i32, UvFrame> {
+        // If the user transform is not invertible, the output coordinates
+        // are used as they are.
+        let to_glyph = self
+            .user_transform
+            .inverse()
+            .unwrap_or_else(lyon::math::Transform::identity);
 
-        for (glyph_id, glyph_path, color) in &self.glyph_paths {
-            let mut geometry: VertexBuffers<Vertex, usize> = VertexBuffers::new();
-            tessellator
-                .tessellate_path(
-                    glyph_path,
-                    &options,
-                    &mut BuffersBuilder::new(&mut geometry, VertexCtor {}),
-                )
-                .unwrap();
-            extract_vertex_buffer(geometry, &mut result, *glyph_id as i32, *color);
+        let mut frames: HashMap<i32, UvFrame> = HashMap::new();
+        for (glyph_id, glyph_path, _) in &self.glyph_paths {
+            let bbox = bounding_box(glyph_path.iter().transformed(&to_glyph));
+            frames
+                .entry(*glyph_id as i32)
+                .and_modify(|frame| frame.bbox = frame.bbox.union(&bbox))
+                .or_insert(UvFrame { to_glyph, bbox });
         }
-        result
+        frames
     }
 
-    /// Convert the outline paths into stroke with a specified line width as triangles.
-    pub fn into_stroke(self) -> PathTibble {
-        let has_color = self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
-        let mut result = PathTibble {
-            x: Vec::new(),
-            y: Vec::new(),
-            glyph_id: Vec::new(),
-            path_id: None,
-            triangle_id: Some(Vec::new()),
-            color: if has_color { Some(Vec::new()) } else { None },
-        };
+    fn tessellate_fill(&self, tessellator: &mut FillTessellator) -> savvy::Result<GlyphBuffers> {
+        let options =
+            FillOptions::tolerance(self.tolerance).with_fill_rule(self.fill_rule.to_lyon());
+
+        self.glyph_paths
+            .iter()
+            .map(|(glyph_id, glyph_path, color)| {
+                let mut geometry: VertexBuffers<Vertex, usize> = VertexBuffers::new();
+                tessellator
+                    .tessellate_path(
+                        glyph_path,
+                        &options,
+                        &mut BuffersBuilder::new(&mut geometry, VertexCtor {}),
+                    )
+                    .map_err(tessellation_error)?;
+                Ok((*glyph_id as i32, geometry, *color))
+            })
+            .collect()
+    }
+
+    fn tessellate_stroke(
+        &self,
+        tessellator: &mut StrokeTessellator,
+    ) -> savvy::Result<GlyphBuffers> {
+        let options = StrokeOptions::tolerance(self.tolerance)
+            .with_line_width(self.line_width)
+            .with_line_join(self.stroke_style.line_join)
+            .with_start_cap(self.stroke_style.start_cap)
+            .with_end_cap(self.stroke_style.end_cap)
+            .with_miter_limit(self.stroke_style.miter_limit);
+
+        self.glyph_paths
+            .iter()
+            .map(|(glyph_id, glyph_path, color)| {
+                // The variable width is applied before dashing so that the
+                // profile follows the whole contour rather than each dash.
+                let mut options = options;
+                let variable = self.stroke_style.variable_width.as_ref().map(|width| {
+                    options = options
+                        .with_line_width(width.glyph_width(*glyph_id))
+                        .with_variable_line_width(0);
+                    width.apply(glyph_path, self.tolerance)
+                });
+                let path = variable.as_ref().unwrap_or(glyph_path);
 
-        let mut tessellator = StrokeTessellator::new();
-        let options = StrokeOptions::tolerance(self.tolerance).with_line_width(self.line_width);
+                let dashed = self
+                    .stroke_style
+                    .dash
+                    .as_ref()
+                    .map(|dash| dash.apply(path, self.tolerance))
+                    .transpose()?;
 
-        for (glyph_id, glyph_path, color) in &self.glyph_paths {
-            let mut geometry: VertexBuffers<Vertex, usize> = VertexBuffers::new();
-            tessellator
-                .tessellate_path(
-                    glyph_path,
-                    &options,
-                    &mut BuffersBuilder::new(&mut geometry, VertexCtor {}),
-                )
-                .unwrap();
-            extract_vertex_buffer(geometry, &mut result, *glyph_id as i32, *color);
+                let mut geometry: VertexBuffers<Vertex, usize> = VertexBuffers::new();
+                tessellator
+                    .tessellate_path(
+                        dashed.as_ref().unwrap_or(path),
+                        &options,
+                        &mut BuffersBuilder::new(&mut geometry, VertexCtor {}),
+                    )
+                    .map_err(tessellation_error)?;
+                Ok((*glyph_id as i32, geometry, *color))
+            })
+            .collect()
+    }
+
+    /// Convert the outline paths into fill as triangles. If `uv` is `true`,
+    /// the texture coordinates relative to the bounding box of each glyph are
+    /// added.
+    ///
+    /// The tessellator is passed from the caller so that it can be reused
+    /// across multiple calls.
+    pub fn into_fill(
+        self,
+        tessellator: &mut FillTessellator,
+        uv: bool,
+    ) -> savvy::Result<PathTibble> {
+        let mut result = self.empty_triangles(false, uv);
+        let uv_frames = if uv { self.uv_frames() } else { HashMap::new() };
+        for (glyph_id, geometry, color) in self.tessellate_fill(tessellator)? {
+            let uv_frame = uv_frames.get(&glyph_id);
+            extract_vertex_buffer(geometry, &mut result, glyph_id, color, uv_frame);
         }
-        result
+        Ok(result)
+    }
+
+    /// Convert the outline paths into stroke with a specified line width as
+    /// triangles. If `vertex_attributes` is `true`, the normal, the
+    /// advancement, and the side of each vertex are added.
+    ///
+    /// The tessellator is passed from the caller so that it can be reused
+    /// across multiple calls.
+    pub fn into_stroke(
+        self,
+        tessellator: &mut StrokeTessellator,
+        vertex_attributes: bool,
+    ) -> savvy::Result<PathTibble> {
+        let mut result = self.empty_triangles(vertex_attributes, false);
+        for (glyph_id, geometry, color) in self.tessellate_stroke(tessellator)? {
+            extract_vertex_buffer(geometry, &mut result, glyph_id, color, None);
+        }
+        Ok(result)
+    }
+
+    /// Convert the outline paths into fill as an indexed mesh, i.e., the
+    /// vertices and the triangles referring to them.
+    pub fn into_fill_mesh(self, tessellator: &mut FillTessellator) -> savvy::Result<MeshTibble> {
+        let has_color = self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+        let mut result = MeshTibble::new(has_color);
+        for (glyph_id, geometry, color) in self.tessellate_fill(tessellator)? {
+            extract_mesh(geometry, &mut result, glyph_id, color);
+        }
+        Ok(result)
+    }
+
+    /// Convert the outline paths into stroke as an indexed mesh, i.e., the
+    /// vertices and the triangles referring to them.
+    pub fn into_stroke_mesh(
+        self,
+        tessellator: &mut StrokeTessellator,
+    ) -> savvy::Result<MeshTibble> {
+        let has_color = self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+        let mut result = MeshTibble::new(has_color);
+        for (glyph_id, geometry, color) in self.tessellate_stroke(tessellator)? {
+            extract_mesh(geometry, &mut result, glyph_id, color);
+        }
+        Ok(result)
     }
 }
 
@@ -87,23 +619,66 @@ fn extract_vertex_buffer(
     dst: &mut PathTibble,
     glyph_id: i32,
     paint_color: Option<RgbaColor>,
+    uv_frame: Option<&UvFrame>,
 ) {
     let offset = dst.triangle_id.as_ref().map_or(0, |v| match v.last() {
         Some(last_triangle_id) => last_triangle_id + 1,
         None => 0,
     });
     let color_str = dst.color.as_ref().map(|_| color_to_hex(paint_color));
+
     for (n, &i) in geometry.indices.iter().enumerate() {
         if let Some(v) = geometry.vertices.get(i) {
-            dst.x.push(v.0.x as _);
-            dst.y.push(v.0.y as _);
+            dst.x.push(v.position.x as _);
+            dst.y.push(v.position.y as _);
             dst.glyph_id.push(glyph_id);
             if let Some(triangle_id) = &mut dst.triangle_id {
                 triangle_id.push(n as i32 / 3 + offset);
             }
+            if let Some(stroke) = &mut dst.stroke_attributes {
+                stroke.normal_x.push(v.normal.x as _);
+                stroke.normal_y.push(v.normal.y as _);
+                stroke.advancement.push(v.advancement as _);
+                stroke.side.push(match v.side {
+                    Side::Positive => "left",
+                    Side::Negative => "right",
+                });
+            }
+            if let Some(uv) = &mut dst.uv {
+                let (u, v) = uv_frame.map_or((0., 0.), |frame| frame.uv(v.position));
+                uv.u.push(u);
+                uv.v.push(v);
+            }
             if let Some(color) = &mut dst.color {
                 color.push(color_str.as_ref().unwrap().clone());
             }
         }
     }
 }
+
+fn extract_mesh(
+    geometry: VertexBuffers<Vertex, usize>,
+    dst: &mut MeshTibble,
+    glyph_id: i32,
+    paint_color: Option<RgbaColor>,
+) {
+    // Indices are 1-based in R.
+    let offset = dst.x.len() as i32 + 1;
+
+    let color_str = dst.color.as_ref().map(|_| color_to_hex(paint_color));
+    for v in &geometry.vertices {
+        dst.x.push(v.position.x as _);
+        dst.y.push(v.position.y as _);
+        dst.glyph_id.push(glyph_id);
+        if let Some(color) = &mut dst.color {
+            color.push(color_str.as_ref().unwrap().clone());
+        }
+    }
+
+    for triangle in geometry.indices.chunks_exact(3) {
+        dst.i.push(triangle[0] as i32 + offset);
+        dst.j.push(triangle[1] as i32 + offset);
+        dst.k.push(triangle[2] as i32 + offset);
+        dst.triangle_glyph_id.push(glyph_id);
+    }
+}
@@ -1,19 +1,60 @@
 use i_overlay::core::fill_rule::FillRule;
 use i_overlay::float::simplify::SimplifyShape;
 
-use crate::builder::{LyonPathBuilderForPath, color_to_hex};
+use crate::builder::{LyonPathBuilderForPath, RgbaColor, color_to_hex};
+use crate::resample::{Resample, Sampling, resample_shapes, sample_shapes};
 use crate::result::PathTibble;
 
-impl LyonPathBuilderForPath {
-    pub fn into_path(self) -> PathTibble {
-        let has_color = self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+/// Shapes of a glyph. Each shape consists of an outer contour followed by the
+/// contours of its holes. The outer contour is counter-clockwise and the holes
+/// are clockwise. The contours are implicitly closed; the first point is not
+/// repeated at the end.
+pub type Shapes = Vec<Vec<Vec<[f32; 2]>>>;
+
+/// Per-glyph shapes. Each entry holds (glyph_id, shapes, optional COLR color).
+pub type GlyphShapes = Vec<(u32, Shapes, Option<RgbaColor>)>;
+
+/// How to determine the inside of the outlines.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutlineFillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+    /// Keep the contours as they are in the font, i.e., don't merge the
+    /// overlapping contours nor normalize the directions. This is only for the
+    /// paths.
+    Raw,
+}
+
+impl TryFrom<&str> for OutlineFillRule {
+    type Error = savvy::Error;
+
+    fn try_from(value: &str) -> savvy::Result<Self> {
+        match value {
+            "nonzero" => Ok(Self::NonZero),
+            "evenodd" => Ok(Self::EvenOdd),
+            "none" => Ok(Self::Raw),
+            _ => Err(savvy::Error::new(format!("Unknown fill rule: {value}"))),
+        }
+    }
+}
+
+impl OutlineFillRule {
+    pub fn to_lyon(self) -> lyon::tessellation::FillRule {
+        match self {
+            OutlineFillRule::NonZero | OutlineFillRule::Raw => {
+                lyon::tessellation::FillRule::NonZero
+            }
+            OutlineFillRule::EvenOdd => lyon::tessellation::FillRule::EvenOdd,
+        }
+    }
+}
 
-        let mut x = Vec::new();
-        let mut y = Vec::new();
-        let mut glyph_id = Vec::new();
-        let mut path_id = Vec::new();
-        let mut color_vec: Vec<String> = Vec::new();
-        let mut out_path_id: u32 = 0;
+impl LyonPathBuilderForPath {
+    /// Merge the contours of each glyph into shapes according to the fill
+    /// rule. For `OutlineFillRule::Raw`, each contour is a shape by itself.
+    pub fn glyph_shapes(&self) -> GlyphShapes {
+        let mut result = Vec::new();
 
         for (gid, glyph_path, paint_color) in &self.glyph_paths {
             let mut contours: Vec<Vec<[f32; 2]>> = Vec::new();
@@ -39,6 +80,7 @@ impl LyonPathBuilderForPath {
                         }
                     }
                     // Quadratic / Cubic do not appear in a FlattenedPathBuilder output.
+                    // Use into_curves() to get them.
                     _ => {}
                 }
             }
@@ -47,52 +89,193 @@ impl LyonPathBuilderForPath {
                 continue;
             }
 
-            // simplify_shape(NonZero) merges overlapping contours (e.g. components
+            // simplify_shape() merges overlapping contours (e.g. components
             // of a composite glyph) while preserving counter-shapes (holes).
-            let merged = contours.simplify_shape(FillRule::NonZero);
-
-            let color_str = if has_color {
-                Some(color_to_hex(*paint_color))
-            } else {
-                None
+            let merged = match self.fill_rule {
+                OutlineFillRule::NonZero => contours.simplify_shape(FillRule::NonZero),
+                OutlineFillRule::EvenOdd => contours.simplify_shape(FillRule::EvenOdd),
+                OutlineFillRule::Raw => contours
+                    .into_iter()
+                    .map(|mut contour| {
+                        // The closing point is added by shapes_to_path().
+                        if contour.first() == contour.last() {
+                            contour.pop();
+                        }
+                        vec![contour]
+                    })
+                    .collect(),
             };
 
-            for shape in merged {
-                for contour in shape {
-                    if contour.is_empty() {
-                        continue;
-                    }
-                    out_path_id += 1;
-                    let first = contour[0];
-                    let n_points = contour.len() + 1; // contour points + closing point
-                    for pt in &contour {
-                        x.push(pt[0] as f64);
-                        y.push(pt[1] as f64);
-                        glyph_id.push(*gid as i32);
-                        path_id.push(out_path_id as i32);
-                    }
-                    // i_overlay returns implicitly-closed contours (no repeated first
-                    // point). Append the first point again so geom_path() draws a
-                    // closed polygon.
-                    x.push(first[0] as f64);
-                    y.push(first[1] as f64);
-                    glyph_id.push(*gid as i32);
+            result.push((*gid, merged, *paint_color));
+        }
+
+        result
+    }
+
+    pub fn into_path(self) -> savvy::Result<PathTibble> {
+        self.into_path_with(GlyphUnion::None, Resample::None)
+    }
+
+    /// Same as `into_path()`, but merges the shapes across glyphs and adds
+    /// points to the contours.
+    pub fn into_path_with(
+        self,
+        union: GlyphUnion,
+        resample: Resample,
+    ) -> savvy::Result<PathTibble> {
+        let has_color =
+            union != GlyphUnion::All && self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+        let shapes = resample_shapes(union_glyphs(self.glyph_shapes(), union), resample)?;
+        let mut result = shapes_to_path(shapes, has_color);
+        // The raw contours are not grouped into shapes.
+        if self.fill_rule == OutlineFillRule::Raw {
+            result.shape_id = None;
+            result.is_hole = None;
+        }
+        Ok(result)
+    }
+
+    /// Converts the outlines into the points sampled along the contours.
+    /// `distance` is the distance along the contour from its first point.
+    pub fn into_points(self, sampling: Sampling) -> savvy::Result<PathTibble> {
+        let has_color = self.glyph_paths.iter().any(|(_, _, c)| c.is_some());
+        let (shapes, distance) = sample_shapes(self.glyph_shapes(), sampling)?;
+        let mut result = shapes_to_path(shapes, has_color);
+        result.distance = Some(distance);
+        Ok(result)
+    }
+}
+
+/// How to merge the shapes across glyphs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GlyphUnion {
+    /// Keep the shapes of each glyph as they are.
+    None,
+    /// Merge all the glyphs into one.
+    All,
+    /// Merge the consecutive glyphs of the same color.
+    Color,
+}
+
+impl TryFrom<&str> for GlyphUnion {
+    type Error = savvy::Error;
+
+    fn try_from(value: &str) -> savvy::Result<Self> {
+        match value {
+            "none" => Ok(Self::None),
+            "all" => Ok(Self::All),
+            "color" => Ok(Self::Color),
+            _ => Err(savvy::Error::new(format!("Unknown union mode: {value}"))),
+        }
+    }
+}
+
+/// Merges the overlapping shapes across glyphs so that no contours overlap.
+/// For `GlyphUnion::Color`, only the consecutive glyphs (or layers) of the
+/// same color are merged so that the paint order is kept. The merged glyphs
+/// are numbered from 1 in the paint order. Colors are dropped for
+/// `GlyphUnion::All`.
+pub fn union_glyphs(glyph_shapes: GlyphShapes, mode: GlyphUnion) -> GlyphShapes {
+    if mode == GlyphUnion::None {
+        return glyph_shapes;
+    }
+
+    let mut groups: Vec<(Option<RgbaColor>, Shapes)> = Vec::new();
+    for (_, shapes, color) in glyph_shapes {
+        let color = if mode == GlyphUnion::Color {
+            color
+        } else {
+            None
+        };
+        match groups.last_mut().filter(|(c, _)| *c == color) {
+            Some((_, group_shapes)) => group_shapes.extend(shapes),
+            None => groups.push((color, shapes)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(i, (color, shapes))| {
+            // The outer rings are counter-clockwise and the holes are
+            // clockwise, so the non-zero rule fills the union of the shapes.
+            let contours: Vec<Vec<[f32; 2]>> = shapes.into_iter().flatten().collect();
+            (
+                i as u32 + 1,
+                contours.simplify_shape(FillRule::NonZero),
+                color,
+            )
+        })
+        .collect()
+}
+
+/// Converts the shapes into the closed contours. `shape_id` and `is_hole`
+/// tell which contours are the holes of which outer ring.
+pub fn shapes_to_path(glyph_shapes: GlyphShapes, has_color: bool) -> PathTibble {
+    let mut x = Vec::new();
+    let mut y = Vec::new();
+    let mut glyph_id = Vec::new();
+    let mut path_id = Vec::new();
+    let mut shape_id = Vec::new();
+    let mut is_hole = Vec::new();
+    let mut color_vec: Vec<String> = Vec::new();
+    let mut out_path_id: u32 = 0;
+    let mut out_shape_id: u32 = 0;
+
+    for (gid, merged, paint_color) in glyph_shapes {
+        let color_str = if has_color {
+            Some(color_to_hex(paint_color))
+        } else {
+            None
+        };
+
+        for shape in merged {
+            out_shape_id += 1;
+            // The first contour is the outer ring, and the rest are holes.
+            for (i, contour) in shape.into_iter().enumerate() {
+                if contour.is_empty() {
+                    continue;
+                }
+                out_path_id += 1;
+                let first = contour[0];
+                let n_points = contour.len() + 1; // contour points + closing point
+                for pt in &contour {
+                    x.push(pt[0] as f64);
+                    y.push(pt[1] as f64);
+                    glyph_id.push(gid as i32);
                     path_id.push(out_path_id as i32);
+                }
+                // i_overlay returns implicitly-closed contours (no repeated first
+                // point). Append the first point again so geom_path() draws a
+                // closed polygon.
+                x.push(first[0] as f64);
+                y.push(first[1] as f64);
+                glyph_id.push(gid as i32);
+                path_id.push(out_path_id as i32);
 
-                    if let Some(s) = &color_str {
-                        color_vec.extend(std::iter::repeat_n(s.clone(), n_points));
-                    }
+                shape_id.extend(std::iter::repeat_n(out_shape_id as i32, n_points));
+                is_hole.extend(std::iter::repeat_n(i > 0, n_points));
+
+                if let Some(s) = &color_str {
+                    color_vec.extend(std::iter::repeat_n(s.clone(), n_points));
                 }
             }
         }
+    }
 
-        PathTibble {
-            x,
-            y,
-            glyph_id,
-            path_id: Some(path_id),
-            triangle_id: None,
-            color: if has_color { Some(color_vec) } else { None },
-        }
+    PathTibble {
+        x,
+        y,
+        string_id: None,
+        glyph_id,
+        path_id: Some(path_id),
+        shape_id: Some(shape_id),
+        is_hole: Some(is_hole),
+        distance: None,
+        curve: None,
+        triangle_id: None,
+        stroke_attributes: None,
+        uv: None,
+        color: if has_color { Some(color_vec) } else { None },
     }
 }
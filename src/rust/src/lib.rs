@@ -2,159 +2,23 @@ use font::FONT_COLLECTION;
 use result::FontDBTibble;
 use savvy::savvy;
 
+pub mod arrow;
+pub mod boolean;
 pub mod builder;
+pub mod extrude;
 pub mod font;
+pub mod font_face;
+pub mod geojson;
+pub mod geometry;
+pub mod into_curves;
 pub mod into_fill_stroke;
 pub mod into_path;
+pub mod model;
+pub mod offset;
+pub mod resample;
 pub mod result;
-
-enum ConversionType {
-    Path,
-    Stroke,
-    Fill,
-}
-
-fn string2any_family(
-    text: &str,
-    font_family: &str,
-    font_weight: f64,
-    font_style: &str,
-    tolerance: f64,
-    line_width: f64,
-    ct: ConversionType,
-) -> savvy::Result<savvy::Sexp> {
-    let result = match ct {
-        ConversionType::Path => {
-            let mut builder = builder::LyonPathBuilderForPath::new(tolerance as _, line_width as _);
-            builder.outline(text, font_family, font_weight, font_style)?;
-            builder.into_path()
-        }
-        ConversionType::Stroke | ConversionType::Fill => {
-            let mut builder =
-                builder::LyonPathBuilderForStrokeAndFill::new(tolerance as _, line_width as _);
-            builder.outline(text, font_family, font_weight, font_style)?;
-            if matches!(ct, ConversionType::Stroke) {
-                builder.into_stroke()
-            } else {
-                builder.into_fill()
-            }
-        }
-    };
-
-    result.try_into()
-}
-
-fn string2any_file(
-    text: &str,
-    font_file: &str,
-    tolerance: f64,
-    line_width: f64,
-    ct: ConversionType,
-) -> savvy::Result<savvy::Sexp> {
-    let result = match ct {
-        ConversionType::Path => {
-            let mut builder = builder::LyonPathBuilderForPath::new(tolerance as _, line_width as _);
-            builder.outline_from_file(text, font_file)?;
-            builder.into_path()
-        }
-        ConversionType::Stroke | ConversionType::Fill => {
-            let mut builder =
-                builder::LyonPathBuilderForStrokeAndFill::new(tolerance as _, line_width as _);
-            builder.outline_from_file(text, font_file)?;
-            if matches!(ct, ConversionType::Stroke) {
-                builder.into_stroke()
-            } else {
-                builder.into_fill()
-            }
-        }
-    };
-
-    result.try_into()
-}
-
-#[savvy]
-fn string2path_family(
-    text: &str,
-    font_family: &str,
-    font_weight: f64,
-    font_style: &str,
-    tolerance: f64,
-) -> savvy::Result<savvy::Sexp> {
-    string2any_family(
-        text,
-        font_family,
-        font_weight,
-        font_style,
-        tolerance,
-        0.,
-        ConversionType::Path,
-    )
-}
-
-#[savvy]
-fn string2path_file(text: &str, font_file: &str, tolerance: f64) -> savvy::Result<savvy::Sexp> {
-    string2any_file(text, font_file, tolerance, 0., ConversionType::Path)
-}
-
-#[savvy]
-fn string2stroke_family(
-    text: &str,
-    font_family: &str,
-    font_weight: f64,
-    font_style: &str,
-    tolerance: f64,
-    line_width: f64,
-) -> savvy::Result<savvy::Sexp> {
-    string2any_family(
-        text,
-        font_family,
-        font_weight,
-        font_style,
-        tolerance,
-        line_width,
-        ConversionType::Stroke,
-    )
-}
-
-#[savvy]
-fn string2stroke_file(
-    text: &str,
-    font_file: &str,
-    tolerance: f64,
-    line_width: f64,
-) -> savvy::Result<savvy::Sexp> {
-    string2any_file(
-        text,
-        font_file,
-        tolerance,
-        line_width,
-        ConversionType::Stroke,
-    )
-}
-
-#[savvy]
-fn string2fill_family(
-    text: &str,
-    font_family: &str,
-    font_weight: f64,
-    font_style: &str,
-    tolerance: f64,
-) -> savvy::Result<savvy::Sexp> {
-    string2any_family(
-        text,
-        font_family,
-        font_weight,
-        font_style,
-        tolerance,
-        0.,
-        ConversionType::Fill,
-    )
-}
-
-#[savvy]
-fn string2fill_file(text: &str, font_file: &str, tolerance: f64) -> savvy::Result<savvy::Sexp> {
-    string2any_file(text, font_file, tolerance, 0., ConversionType::Fill)
-}
+pub mod skeleton;
+pub mod svg;
 
 #[savvy]
 fn dump_fontdb_impl() -> savvy::Result<savvy::Sexp> {
@@ -200,41 +64,43 @@ fn dump_fontdb_impl() -> savvy::Result<savvy::Sexp> {
     result.try_into()
 }
 
-#[cfg(feature = "savvy_test")]
+#[cfg(all(test, feature = "savvy_test"))]
 mod tests {
-    use crate::builder::LyonPathBuilder;
+    use crate::builder::{LyonPathBuilderForPath, LyonPathBuilderForStrokeAndFill};
 
     #[test]
     fn test_path() {
-        let mut builder = LyonPathBuilder::new(0.00001, 0.);
+        let mut builder = LyonPathBuilderForPath::new(0.00001, 0.);
         builder
             .outline_from_file("A", "test/font/test.ttf")
             .unwrap();
-        let result = builder.into_path();
+        let result = builder.into_path().unwrap();
 
         assert!(
             result
                 .x
                 .iter()
-                .zip(vec![0., 100. / 125., 0., 0.])
-                .all(|(actual, expect)| (expect - actual).abs() < f64::EPSILON.sqrt())
+                .zip(vec![100. / 125., 100. / 125., 200. / 125., 100. / 125.])
+                .all(|(actual, expect)| (expect - actual).abs() < 0.0001)
         );
         assert!(
             result
                 .y
                 .iter()
-                .zip(vec![0., 100. / 125., 100. / 125., 0.])
+                .zip(vec![100. / 125., 0., 100. / 125., 100. / 125.])
                 .all(|(actual, expect)| (expect - actual).abs() < 0.0001)
         );
     }
 
     #[test]
     fn test_stroke() {
-        let mut builder = LyonPathBuilder::new(0.00001, 0.2);
+        let mut builder = LyonPathBuilderForStrokeAndFill::new(0.00001, 0.2);
         builder
             .outline_from_file("A", "test/font/test.ttf")
             .unwrap();
-        let result = builder.into_stroke();
+        let result = builder
+            .into_stroke(&mut lyon::tessellation::StrokeTessellator::new(), false)
+            .unwrap();
 
         assert!(
             result
@@ -252,26 +118,292 @@ mod tests {
 
     #[test]
     fn test_fill() {
-        let mut builder = LyonPathBuilder::new(0.00001, 0.);
+        let mut builder = LyonPathBuilderForStrokeAndFill::new(0.00001, 0.);
         builder
             .outline_from_file("A", "test/font/test.ttf")
             .unwrap();
-        let result = builder.into_fill();
+        let result = builder
+            .into_fill(&mut lyon::tessellation::FillTessellator::new(), false)
+            .unwrap();
 
-        // TODO: Is this correct...?
         assert!(
             result
                 .x
                 .iter()
-                .zip(vec![0., 0., 100. / 125.])
-                .all(|(actual, expect)| (expect - actual).abs() < f64::EPSILON.sqrt())
+                .zip(vec![100. / 125., 100. / 125., 200. / 125.])
+                .all(|(actual, expect)| (expect - actual).abs() < 0.0001)
         );
         assert!(
             result
                 .y
                 .iter()
                 .zip(vec![0., 100. / 125., 100. / 125.])
-                .all(|(actual, expect)| (expect - actual).abs() < f64::EPSILON.sqrt())
+                .all(|(actual, expect)| (expect - actual).abs() < 0.0001)
+        );
+    }
+
+    #[test]
+    fn test_dash_too_short() {
+        use crate::into_fill_stroke::StrokeStyle;
+
+        let stroke = |dash: &[f64], tolerance: f32| {
+            let mut builder = LyonPathBuilderForStrokeAndFill::new(tolerance, 0.01);
+            builder.stroke_style =
+                StrokeStyle::new("miter", "butt", 4., dash, 0., &[0.01], &[]).unwrap();
+            builder
+                .outline_from_file("A", "test/font/test.ttf")
+                .unwrap();
+            builder.into_stroke(&mut lyon::tessellation::StrokeTessellator::new(), false)
+        };
+
+        // These used to hang, or to produce NaN, instead of returning an error
+        assert!(stroke(&[1e-7, 1e-7], 0.00001).is_err());
+        assert!(stroke(&[1e-50, 1e-50], 0.00001).is_err());
+        // A pattern that splits a contour into too many dashes
+        assert!(stroke(&[1e-6, 1e-6], 1e-7).is_err());
+        assert!(stroke(&[0.1, 0.05], 0.00001).is_ok());
+    }
+
+    #[test]
+    fn test_synthetic_bold() {
+        use crate::font::LoadedFont;
+
+        let outline = |embolden: f32| {
+            let mut font = LoadedFont::from_file("test/font/test.ttf").unwrap();
+            font.synthetic_embolden = embolden;
+            let mut builder = LyonPathBuilderForPath::new(0.00001, 0.);
+            builder.outline_font(&font, "A").unwrap();
+            builder.into_path().unwrap()
+        };
+        let range = |v: &[f64]| {
+            v.iter().cloned().fold(f64::INFINITY, f64::min)
+                ..v.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+        };
+
+        let regular = outline(0.);
+        // 10 units of the font whose units per em is 125
+        let bold = outline(10.);
+        let (x0, x1) = (range(&regular.x), range(&bold.x));
+        let (y0, y1) = (range(&regular.y), range(&bold.y));
+
+        // The outline is offset outward by 10 / 125 = 0.08 (or more at the
+        // acute corners)
+        assert!(x1.start <= x0.start - 0.08 + 0.0001);
+        assert!(x1.end >= x0.end + 0.08 - 0.0001);
+        assert!(y1.start <= y0.start - 0.08 + 0.0001);
+        assert!(y1.end >= y0.end + 0.08 - 0.0001);
+    }
+
+    #[test]
+    fn test_synthetic_bold_fill_rule() {
+        use crate::builder::embolden_path;
+        use crate::into_path::OutlineFillRule;
+
+        // Two nested squares in the same direction; the inner one is a hole
+        // only with the even-odd rule
+        let mut builder = lyon::path::Path::builder();
+        for (x0, x1) in [(0., 3.), (1., 2.)] {
+            builder.begin(lyon::math::point(x0, x0));
+            builder.line_to(lyon::math::point(x1, x0));
+            builder.line_to(lyon::math::point(x1, x1));
+            builder.line_to(lyon::math::point(x0, x1));
+            builder.end(true);
+        }
+        let path = builder.build();
+        let n_contours = |fill_rule| {
+            embolden_path(&path, 0.1, 0.00001, fill_rule)
+                .iter()
+                .filter(|e| matches!(e, lyon::path::Event::Begin { .. }))
+                .count()
+        };
+
+        assert_eq!(n_contours(OutlineFillRule::NonZero), 1);
+        assert_eq!(n_contours(OutlineFillRule::EvenOdd), 2);
+    }
+
+    #[test]
+    fn test_relative_tolerance() {
+        use skrifa::outline::OutlinePen;
+
+        // Two circles of radius 1 and 10, whose sizes are 2 and 20
+        let n_points = |relative_tolerance: bool| {
+            let mut builder = LyonPathBuilderForPath::new(0.001, 0.);
+            builder.set_relative_tolerance(relative_tolerance);
+            for r in [1., 10.] {
+                builder.cur_glyph_id += 1;
+                if relative_tolerance {
+                    builder.set_glyph_size(Some(2. * r));
+                }
+                builder.move_to(r, 0.);
+                builder.quad_to(r, r, 0., r);
+                builder.quad_to(-r, r, -r, 0.);
+                builder.quad_to(-r, -r, 0., -r);
+                builder.quad_to(r, -r, r, 0.);
+                builder.close();
+                builder.finish_glyph();
+            }
+            let result = builder.into_path().unwrap();
+            [1, 2].map(|id| result.glyph_id.iter().filter(|&&i| i == id).count())
+        };
+
+        // The larger circle needs more points with the absolute tolerance, but
+        // the same number of points with the relative one
+        let [small, large] = n_points(false);
+        assert!(large > small);
+        let [small, large] = n_points(true);
+        assert_eq!(large, small);
+    }
+
+    #[test]
+    fn test_resample_too_short() {
+        use crate::resample::{Resample, resample_shapes};
+
+        let square = vec![vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]]];
+        let resample = |resample| resample_shapes(vec![(1, vec![square.clone()], None)], resample);
+
+        assert!(resample(Resample::MaxSegmentLength(1e-7)).is_err());
+        assert!(resample(Resample::Uniform(1e-7)).is_err());
+        assert!(resample(Resample::MaxSegmentLength(0.1)).is_ok());
+        assert_eq!(
+            resample(Resample::Uniform(0.1)).unwrap()[0].1[0][0].len(),
+            40
+        );
+    }
+
+    #[test]
+    fn test_sample_too_many() {
+        use crate::resample::{Sampling, sample_shapes};
+
+        let square = vec![vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]]];
+        let sample = |sampling| {
+            let (shapes, _) = sample_shapes(vec![(1, vec![square.clone()], None)], sampling)?;
+            Ok::<_, savvy::Error>(shapes[0].1[0][0].len())
+        };
+
+        assert!(sample(Sampling::Count(1_000_000_000)).is_err());
+        assert!(sample(Sampling::Spacing(1e-9)).is_err());
+        assert_eq!(sample(Sampling::Count(10)).unwrap(), 10);
+        assert_eq!(sample(Sampling::Spacing(0.1)).unwrap(), 40);
+    }
+
+    #[test]
+    fn test_curves() {
+        use skrifa::outline::OutlinePen;
+
+        let mut builder = LyonPathBuilderForStrokeAndFill::new(0.00001, 0.);
+        builder.move_to(0., 0.);
+        builder.quad_to(1., 1., 2., 0.);
+        builder.curve_to(2., -1., 1., -2., 0., -1.);
+        builder.close();
+        builder.finish_glyph();
+        let result = builder.into_curves();
+        let curve = result.curve.unwrap();
+
+        assert_eq!(curve.verb, vec!["move", "quad", "cubic", "close"]);
+        assert_eq!(result.x, vec![0., 2., 0., 0.]);
+        assert_eq!(result.y, vec![0., 0., -1., 0.]);
+        assert_eq!(curve.ctrl1_x, vec![None, Some(1.), Some(2.), None]);
+        assert_eq!(curve.ctrl1_y, vec![None, Some(1.), Some(-1.), None]);
+        assert_eq!(curve.ctrl2_x, vec![None, None, Some(1.), None]);
+        assert_eq!(curve.ctrl2_y, vec![None, None, Some(-2.), None]);
+    }
+
+    #[test]
+    fn test_geometry_by_string_merges_overlaps() {
+        use crate::geometry::{GeometryFormat, GeometryTibble};
+
+        let square = |x: f32| vec![vec![[x, 0.], [x + 1., 0.], [x + 1., 1.], [x, 1.]]];
+        let glyph_shapes = vec![(1, vec![square(0.)], None), (2, vec![square(0.5)], None)];
+        let result = GeometryTibble::by_string(glyph_shapes, GeometryFormat::Wkt);
+
+        // The overlapping glyphs become one polygon without a hole
+        assert_eq!(result.geometry.len(), 1);
+        assert_eq!(result.geometry[0].len(), 1);
+        assert_eq!(result.geometry[0][0].len(), 1);
+        let xs = result.geometry[0][0][0].iter().map(|p| p[0]);
+        assert_eq!(xs.clone().fold(f32::INFINITY, f32::min), 0.);
+        assert_eq!(xs.fold(f32::NEG_INFINITY, f32::max), 1.5);
+    }
+
+    #[test]
+    fn test_union_color_keeps_paint_order() {
+        use crate::builder::RgbaColor;
+        use crate::into_path::{GlyphUnion, union_glyphs};
+
+        let red = Some(RgbaColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+            alpha: 255,
+        });
+        let blue = Some(RgbaColor {
+            red: 0,
+            green: 0,
+            blue: 255,
+            alpha: 255,
+        });
+        let square = |x: f32| vec![vec![[x, 0.], [x + 1., 0.], [x + 1., 1.], [x, 1.]]];
+        let glyph_shapes = vec![
+            (1, vec![square(0.)], red),
+            (2, vec![square(0.5)], red),
+            (3, vec![square(1.)], blue),
+            (4, vec![square(1.5)], red),
+        ];
+
+        // The overlapping red squares are merged, but the last red one is
+        // painted over the blue one, so it stays separate
+        let result = union_glyphs(glyph_shapes.clone(), GlyphUnion::Color);
+        let ids: Vec<_> = result.iter().map(|(id, _, _)| *id).collect();
+        let colors: Vec<_> = result.iter().map(|(_, _, color)| *color).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(colors, vec![red, blue, red]);
+        assert_eq!(result[0].1.len(), 1);
+        assert_eq!(result[0].1[0].len(), 1);
+
+        // All the squares overlap, so they become one contour
+        let result = union_glyphs(glyph_shapes, GlyphUnion::All);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].1.len(), 1);
+        assert_eq!(result[0].1[0].len(), 1);
+        assert_eq!(result[0].2, None);
+    }
+
+    #[test]
+    fn test_extrude_bevel_is_closed() {
+        use crate::extrude::extrude;
+
+        // A square with a square hole
+        let outer = vec![[0., 0.], [3., 0.], [3., 3.], [0., 3.]];
+        let hole = vec![[1., 1.], [1., 2.], [2., 2.], [2., 1.]];
+        let glyph_shapes = vec![(1, vec![vec![outer, hole]], None)];
+        let mesh = extrude(
+            &glyph_shapes,
+            1.,
+            0.2,
+            &mut lyon::tessellation::FillTessellator::new(),
+            0.00001,
+        )
+        .unwrap();
+
+        // Each edge is shared by exactly two triangles in the opposite
+        // directions
+        let mut edges = std::collections::HashMap::new();
+        for [i, j, k] in &mesh.triangles {
+            for edge in [(*i, *j), (*j, *k), (*k, *i)] {
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        assert!(
+            edges
+                .iter()
+                .all(|(&(i, j), &n)| n == 1 && edges.get(&(j, i)) == Some(&1))
         );
     }
+
+    #[test]
+    fn test_gltf_empty() {
+        let gltf = crate::model::write_gltf(&crate::extrude::Mesh3D::default());
+        assert!(!gltf.contains("accessors"));
+        assert!(!gltf.contains("\"count\":0"));
+    }
 }
@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use crate::extrude::Mesh3D;
+
+/// Writes the mesh as a Wavefront OBJ.
+pub fn write_obj(mesh: &Mesh3D) -> String {
+    let mut out = String::new();
+    for [x, y, z] in &mesh.vertices {
+        writeln!(out, "v {x} {y} {z}").unwrap();
+    }
+    // Indices are 1-based in OBJ.
+    for [i, j, k] in &mesh.triangles {
+        writeln!(out, "f {} {} {}", i + 1, j + 1, k + 1).unwrap();
+    }
+    out
+}
+
+fn normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if len == 0. {
+        [0., 0., 0.]
+    } else {
+        n.map(|e| e / len)
+    }
+}
+
+/// Writes the mesh as a binary STL.
+pub fn write_stl(mesh: &Mesh3D) -> Vec<u8> {
+    let mut out = vec![0u8; 80];
+    out[..11].copy_from_slice(b"string2path");
+    out.extend((mesh.triangles.len() as u32).to_le_bytes());
+
+    for t in &mesh.triangles {
+        let [a, b, c] = t.map(|i| mesh.vertices[i as usize]);
+        for v in [normal(a, b, c), a, b, c] {
+            for e in v {
+                out.extend(e.to_le_bytes());
+            }
+        }
+        // attribute byte count
+        out.extend(0u16.to_le_bytes());
+    }
+
+    out
+}
+
+fn to_base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Writes the mesh as a glTF 2.0 JSON. The binary data is embedded as a data
+/// URI so that the file is self-contained.
+pub fn write_gltf(mesh: &Mesh3D) -> String {
+    // An accessor must have at least one element, so an empty mesh is omitted
+    // and the scene has no nodes.
+    if mesh.triangles.is_empty() {
+        return r#"{"asset":{"version":"2.0","generator":"string2path"},"scene":0,"scenes":[{}]}"#
+            .to_string();
+    }
+
+    let mut buffer: Vec<u8> = Vec::new();
+    for v in &mesh.vertices {
+        for e in v {
+            buffer.extend(e.to_le_bytes());
+        }
+    }
+    let positions_len = buffer.len();
+    for t in &mesh.triangles {
+        for i in t {
+            buffer.extend(i.to_le_bytes());
+        }
+    }
+    let indices_len = buffer.len() - positions_len;
+
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for v in &mesh.vertices {
+        for d in 0..3 {
+            min[d] = min[d].min(v[d]);
+            max[d] = max[d].max(v[d]);
+        }
+    }
+
+    // 5126 is FLOAT, 5125 is UNSIGNED_INT, 34962 is ARRAY_BUFFER, and 34963
+    // is ELEMENT_ARRAY_BUFFER.
+    format!(
+        r#"{{"asset":{{"version":"2.0","generator":"string2path"}},"scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0}}],"meshes":[{{"primitives":[{{"attributes":{{"POSITION":0}},"indices":1,"mode":4}}]}}],"accessors":[{{"bufferView":0,"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}},{{"bufferView":1,"componentType":5125,"count":{},"type":"SCALAR"}}],"bufferViews":[{{"buffer":0,"byteOffset":0,"byteLength":{},"target":34962}},{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34963}}],"buffers":[{{"byteLength":{},"uri":"data:application/octet-stream;base64,{}"}}]}}"#,
+        mesh.vertices.len(),
+        min[0],
+        min[1],
+        min[2],
+        max[0],
+        max[1],
+        max[2],
+        mesh.triangles.len() * 3,
+        positions_len,
+        positions_len,
+        indices_len,
+        buffer.len(),
+        to_base64(&buffer),
+    )
+}
@@ -0,0 +1,119 @@
+use i_overlay::mesh::outline::offset::OutlineOffset;
+use i_overlay::mesh::style::{LineJoin, OutlineStyle};
+use lyon::tessellation::FillTessellator;
+
+use crate::builder::color_to_hex;
+use crate::extrude::triangulate;
+use crate::into_path::GlyphShapes;
+use crate::result::PathTibble;
+
+/// The join style of the offset outlines.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OffsetJoin {
+    Round,
+    Miter,
+    Bevel,
+}
+
+impl TryFrom<&str> for OffsetJoin {
+    type Error = savvy::Error;
+
+    fn try_from(value: &str) -> savvy::Result<Self> {
+        match value {
+            "round" => Ok(Self::Round),
+            "miter" => Ok(Self::Miter),
+            "bevel" => Ok(Self::Bevel),
+            _ => Err(savvy::Error::new(format!("Unknown line join: {value}"))),
+        }
+    }
+}
+
+impl OffsetJoin {
+    fn to_line_join(self, distance: f32, tolerance: f32) -> LineJoin<f32> {
+        match self {
+            // The parameter is the ratio of the length of the segments to the
+            // radius. Choose it so that the arc deviates from the segments by
+            // no more than `tolerance`.
+            OffsetJoin::Round => {
+                let radius = distance.abs().max(f32::EPSILON);
+                LineJoin::Round(2. * (2. * tolerance / radius).sqrt())
+            }
+            // The corners sharper than this angle are beveled. This is the same
+            // as the synthetic bold.
+            OffsetJoin::Miter => LineJoin::Miter(std::f32::consts::PI / 3.),
+            OffsetJoin::Bevel => LineJoin::Bevel,
+        }
+    }
+}
+
+/// Offsets the shapes of each glyph by `distance`. A positive distance grows
+/// the shapes (outset), and a negative distance shrinks them (inset). The
+/// glyphs that vanish by the inset are dropped.
+pub fn offset_shapes(
+    glyph_shapes: GlyphShapes,
+    distance: f32,
+    join: OffsetJoin,
+    tolerance: f32,
+) -> GlyphShapes {
+    if distance == 0. {
+        return glyph_shapes;
+    }
+
+    let style = OutlineStyle::new(distance).line_join(join.to_line_join(distance, tolerance));
+    glyph_shapes
+        .into_iter()
+        .filter_map(|(gid, shapes, color)| {
+            let offset = shapes.outline(&style);
+            (!offset.is_empty()).then_some((gid, offset, color))
+        })
+        .collect()
+}
+
+/// Triangulates the shapes of each glyph, in the same form as the fill.
+pub fn shapes_to_fill(
+    glyph_shapes: GlyphShapes,
+    has_color: bool,
+    tessellator: &mut FillTessellator,
+    tolerance: f32,
+) -> savvy::Result<PathTibble> {
+    let mut x = Vec::new();
+    let mut y = Vec::new();
+    let mut glyph_id = Vec::new();
+    let mut triangle_id = Vec::new();
+    let mut color = Vec::new();
+    let mut out_triangle_id = 0;
+
+    for (gid, shapes, paint_color) in glyph_shapes {
+        let geometry = triangulate(&shapes, tessellator, tolerance)?;
+        let color_str = color_to_hex(paint_color);
+        for triangle in geometry.indices.chunks_exact(3) {
+            for &i in triangle {
+                let pt = geometry.vertices[i as usize];
+                x.push(pt[0] as f64);
+                y.push(pt[1] as f64);
+                glyph_id.push(gid as i32);
+                triangle_id.push(out_triangle_id);
+                if has_color {
+                    color.push(color_str.clone());
+                }
+            }
+            out_triangle_id += 1;
+        }
+    }
+
+    Ok(PathTibble {
+        x,
+        y,
+        string_id: None,
+        glyph_id,
+        path_id: None,
+        shape_id: None,
+        is_hole: None,
+        distance: None,
+        curve: None,
+        triangle_id: Some(triangle_id),
+        stroke_attributes: None,
+        uv: None,
+        color: if has_color { Some(color) } else { None },
+    })
+}
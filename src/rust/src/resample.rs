@@ -0,0 +1,203 @@
+use crate::into_path::{GlyphShapes, Shapes};
+
+/// How to add points to the flattened contours.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Resample {
+    /// Keep the points of flattening as they are.
+    None,
+    /// Split the segments longer than this length into equal pieces.
+    MaxSegmentLength(f32),
+    /// Replace the points with the evenly spaced ones whose spacing is at most
+    /// this length.
+    Uniform(f32),
+}
+
+/// The maximum number of the points in a contour, to avoid exhausting the
+/// memory with a segment length that is too short.
+const MAX_POINTS_PER_CONTOUR: usize = 1_000_000;
+
+/// Checks that the number of the points in a contour doesn't exceed
+/// `MAX_POINTS_PER_CONTOUR`. `problem` describes the argument to blame.
+fn check_n_points(n: f64, problem: &str) -> savvy::Result<usize> {
+    if n.is_nan() || n > MAX_POINTS_PER_CONTOUR as f64 {
+        return Err(savvy::Error::new(format!(
+            "{problem}; a contour would have more than {MAX_POINTS_PER_CONTOUR} points"
+        )));
+    }
+    Ok(n as usize)
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f64 {
+    (b[0] as f64 - a[0] as f64).hypot(b[1] as f64 - a[1] as f64)
+}
+
+/// Iterates over the segments of the closed contour, including the closing
+/// segment from the last point to the first point.
+fn segments(contour: &[[f32; 2]]) -> impl Iterator<Item = ([f32; 2], [f32; 2])> + '_ {
+    contour
+        .iter()
+        .zip(contour.iter().skip(1).chain(contour.first()))
+        .map(|(&a, &b)| (a, b))
+}
+
+/// Returns the length of the closed contour.
+pub fn contour_length(contour: &[[f32; 2]]) -> f64 {
+    segments(contour).map(|(a, b)| distance(a, b)).sum()
+}
+
+/// Splits the segments of the closed contour that are longer than
+/// `max_length` into equal pieces.
+fn subdivide(contour: &[[f32; 2]], max_length: f32) -> savvy::Result<Vec<[f32; 2]>> {
+    let counts: Vec<f64> = segments(contour)
+        .map(|(a, b)| (distance(a, b) / max_length as f64).ceil().max(1.))
+        .collect();
+    let total = check_n_points(counts.iter().sum(), "max_segment_length is too short")?;
+
+    let mut result = Vec::with_capacity(total);
+    for ((a, b), n) in segments(contour).zip(counts) {
+        let n = n as usize;
+        for i in 0..n {
+            let t = i as f32 / n as f32;
+            result.push([a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]);
+        }
+    }
+    Ok(result)
+}
+
+/// Returns `n` points evenly spaced along the closed contour, starting from
+/// the first point of the contour.
+pub fn resample_contour(contour: &[[f32; 2]], n: usize) -> Vec<[f32; 2]> {
+    let length = contour_length(contour);
+    let targets: Vec<f64> = (0..n).map(|i| i as f64 * length / n as f64).collect();
+    points_at(contour, &targets)
+}
+
+/// Returns the points at the distances along the closed contour from its first
+/// point. `targets` must be sorted in ascending order.
+fn points_at(contour: &[[f32; 2]], targets: &[f64]) -> Vec<[f32; 2]> {
+    // Close the contour explicitly to make the segments easy to iterate.
+    let points: Vec<[f32; 2]> = contour.iter().chain(contour.first()).copied().collect();
+    let cumulative = cumulative_distances(&points);
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let mut result = Vec::with_capacity(targets.len());
+    let mut j = 0;
+    for &target in targets {
+        // Find the segment (points[j], points[j + 1]) containing the target.
+        while j + 2 < points.len() && cumulative[j + 1] <= target {
+            j += 1;
+        }
+        let (a, b) = (points[j], points[j + 1]);
+        let segment_length = cumulative[j + 1] - cumulative[j];
+        let t = if segment_length > 0. {
+            ((target - cumulative[j]) / segment_length).clamp(0., 1.) as f32
+        } else {
+            0.
+        };
+        result.push([a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]);
+    }
+    result
+}
+
+/// Returns the distances of the points from the first point along the
+/// polyline.
+pub fn cumulative_distances(points: &[[f32; 2]]) -> Vec<f64> {
+    let mut total = 0.;
+    std::iter::once(0.)
+        .chain(points.windows(2).map(|w| {
+            total += distance(w[0], w[1]);
+            total
+        }))
+        .take(points.len())
+        .collect()
+}
+
+/// Adds points to the contours of the shapes.
+pub fn resample_shapes(
+    glyph_shapes: GlyphShapes,
+    resample: Resample,
+) -> savvy::Result<GlyphShapes> {
+    if resample == Resample::None {
+        return Ok(glyph_shapes);
+    }
+
+    let resample_contour = |contour: Vec<[f32; 2]>| match resample {
+        Resample::None => Ok(contour),
+        Resample::MaxSegmentLength(max_length) => subdivide(&contour, max_length),
+        Resample::Uniform(max_length) => {
+            let n = (contour_length(&contour) / max_length as f64).ceil();
+            let n = check_n_points(n, "max_segment_length is too short")?;
+            // A contour needs at least 3 points to be a polygon.
+            Ok(resample_contour(&contour, n.max(3)))
+        }
+    };
+
+    glyph_shapes
+        .into_iter()
+        .map(|(gid, shapes, color)| {
+            let shapes = shapes
+                .into_iter()
+                .map(|shape| shape.into_iter().map(resample_contour).collect())
+                .collect::<savvy::Result<Shapes>>()?;
+            Ok((gid, shapes, color))
+        })
+        .collect()
+}
+
+/// How to sample the points along the contours.
+#[derive(Clone, Copy)]
+pub enum Sampling {
+    /// The number of the points per contour.
+    Count(usize),
+    /// The distance between the points. The last gap of each contour is
+    /// shorter than this.
+    Spacing(f64),
+}
+
+/// Samples the points along the contours of the shapes. In addition to the
+/// shapes, this returns the distance of each point from the first point of
+/// the contour, including the closing point, in the same order as the rows of
+/// `shapes_to_path()`.
+pub fn sample_shapes(
+    glyph_shapes: GlyphShapes,
+    sampling: Sampling,
+) -> savvy::Result<(GlyphShapes, Vec<f64>)> {
+    let mut distances = Vec::new();
+    let mut sample_contour = |contour: Vec<[f32; 2]>| {
+        let length = contour_length(&contour);
+        let targets: Vec<f64> = match sampling {
+            Sampling::Count(n) => {
+                let n = check_n_points(n as f64, "n is too large")?;
+                (0..n).map(|i| i as f64 * length / n as f64).collect()
+            }
+            Sampling::Spacing(spacing) => {
+                let n = check_n_points((length / spacing).ceil(), "spacing is too short")?;
+                (0..n).map(|i| i as f64 * spacing).collect()
+            }
+        };
+        // shapes_to_path() skips the empty contours.
+        if length == 0. || targets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let points = points_at(&contour, &targets);
+        distances.extend(targets);
+        // The closing point
+        distances.push(length);
+        Ok(points)
+    };
+
+    let glyph_shapes = glyph_shapes
+        .into_iter()
+        .map(|(gid, shapes, color)| {
+            let shapes = shapes
+                .into_iter()
+                .map(|shape| shape.into_iter().map(&mut sample_contour).collect())
+                .collect::<savvy::Result<Shapes>>()?;
+            Ok((gid, shapes, color))
+        })
+        .collect::<savvy::Result<GlyphShapes>>()?;
+
+    Ok((glyph_shapes, distances))
+}
@@ -1,4 +1,8 @@
-use savvy::{OwnedIntegerSexp, OwnedRealSexp, OwnedStringSexp};
+use savvy::{
+    NotAvailableValue, OwnedIntegerSexp, OwnedLogicalSexp, OwnedRealSexp, OwnedStringSexp,
+};
+
+use crate::builder::color_to_hex;
 
 /// An intermediate form of the extracted path information to convert to a tibble.
 pub struct PathTibble {
@@ -6,29 +10,256 @@ pub struct PathTibble {
     pub x: Vec<f64>,
     // Unscaled position of y.
     pub y: Vec<f64>,
+    // IDs to distinguish the strings. This field is `None` when the input is a single string.
+    pub string_id: Option<Vec<i32>>,
     // IDs to distinguish the glyphs. Note that this is a different ID than [ttf_parser::GlyphId].
     pub glyph_id: Vec<i32>,
     // IDs to distinguish the groups of paths (i.e., `Begin` path event to `End` path event).
     pub path_id: Option<Vec<i32>>,
+    // IDs to distinguish the shapes (an outer ring and its holes). This field is `Some` only for `ConversionType::Path`.
+    pub shape_id: Option<Vec<i32>>,
+    // Whether the path is a hole of the shape. This field is `Some` only for `ConversionType::Path`.
+    pub is_hole: Option<Vec<bool>>,
+    // The distance along the contour from its first point. This field is `Some` only for the sampled points.
+    pub distance: Option<Vec<f64>>,
+    // Path verbs and control points. This field is `Some` only for the curves.
+    pub curve: Option<CurveColumns>,
     // IDs to distinguish the triangles. This field is `None` for `ConversionType::Path`.
     pub triangle_id: Option<Vec<i32>>,
+    // Per-vertex attributes of the stroke. This field is `Some` only when requested.
+    pub stroke_attributes: Option<StrokeColumns>,
+    // Texture coordinates of the fill. This field is `Some` only when requested.
+    pub uv: Option<UvColumns>,
     // Color of color emoji font.
     pub color: Option<Vec<String>>,
 }
 
+/// The columns specific to the curves. Each row is a path event, and `x` and
+/// `y` of [PathTibble] are the end point of the segment.
+#[derive(Default)]
+pub struct CurveColumns {
+    // One of "move", "line", "quad", "cubic", and "close".
+    pub verb: Vec<&'static str>,
+    // The first control point. NA for "move", "line", and "close".
+    pub ctrl1_x: Vec<Option<f64>>,
+    pub ctrl1_y: Vec<Option<f64>>,
+    // The second control point. NA except for "cubic".
+    pub ctrl2_x: Vec<Option<f64>>,
+    pub ctrl2_y: Vec<Option<f64>>,
+}
+
+impl CurveColumns {
+    const NAMES: [&'static str; 5] = ["verb", "ctrl1_x", "ctrl1_y", "ctrl2_x", "ctrl2_y"];
+
+    fn extend(&mut self, other: CurveColumns) {
+        self.verb.extend(other.verb);
+        self.ctrl1_x.extend(other.ctrl1_x);
+        self.ctrl1_y.extend(other.ctrl1_y);
+        self.ctrl2_x.extend(other.ctrl2_x);
+        self.ctrl2_y.extend(other.ctrl2_y);
+    }
+}
+
+/// The per-vertex attributes of the stroke, which lyon computes while
+/// tessellating.
+#[derive(Default)]
+pub struct StrokeColumns {
+    // The extrusion direction of the vertex from the path. The vertex is at
+    // (the point on the path) + normal * line_width / 2. Note that this is not
+    // a unit vector at joins.
+    pub normal_x: Vec<f64>,
+    pub normal_y: Vec<f64>,
+    // The distance from the start of the sub-path.
+    pub advancement: Vec<f64>,
+    // Either "left" or "right" of the path.
+    pub side: Vec<&'static str>,
+}
+
+impl StrokeColumns {
+    fn extend(&mut self, other: StrokeColumns) {
+        self.normal_x.extend(other.normal_x);
+        self.normal_y.extend(other.normal_y);
+        self.advancement.extend(other.advancement);
+        self.side.extend(other.side);
+    }
+}
+
+/// The texture coordinates of the fill. (0, 0) is the bottom-left corner of
+/// the bounding box of the glyph, and (1, 1) is the top-right corner.
+#[derive(Default)]
+pub struct UvColumns {
+    pub u: Vec<f64>,
+    pub v: Vec<f64>,
+}
+
+impl UvColumns {
+    fn extend(&mut self, other: UvColumns) {
+        self.u.extend(other.u);
+        self.v.extend(other.v);
+    }
+}
+
+/// A column of a tibble.
+pub enum Column {
+    Real(Vec<f64>),
+    // A double column that might contain NA.
+    NullableReal(Vec<Option<f64>>),
+    Integer(Vec<i32>),
+    Logical(Vec<bool>),
+    Character(Vec<String>),
+    StaticCharacter(Vec<&'static str>),
+}
+
+impl TryFrom<Column> for savvy::Sexp {
+    type Error = savvy::Error;
+
+    fn try_from(value: Column) -> savvy::Result<Self> {
+        match value {
+            Column::Real(v) => <OwnedRealSexp>::try_from(v.as_slice())?.into(),
+            Column::NullableReal(v) => {
+                let v: Vec<f64> = v.into_iter().map(|v| v.unwrap_or(f64::na())).collect();
+                <OwnedRealSexp>::try_from(v.as_slice())?.into()
+            }
+            Column::Integer(v) => <OwnedIntegerSexp>::try_from(v.as_slice())?.into(),
+            Column::Logical(v) => <OwnedLogicalSexp>::try_from(v.as_slice())?.into(),
+            Column::Character(v) => <OwnedStringSexp>::try_from(v.as_slice())?.into(),
+            Column::StaticCharacter(v) => <OwnedStringSexp>::try_from(v.as_slice())?.into(),
+        }
+    }
+}
+
 impl PathTibble {
-    fn len(&self) -> usize {
-        let mut len = 3;
-        if self.path_id.is_some() {
-            len += 1
-        };
-        if self.triangle_id.is_some() {
-            len += 1
-        };
-        if self.color.is_some() {
-            len += 1
-        };
-        len
+    /// Returns the columns in the order of the output. The optional columns
+    /// are omitted when they are `None`.
+    pub fn into_columns(self) -> Vec<(&'static str, Column)> {
+        let mut columns = vec![("x", Column::Real(self.x)), ("y", Column::Real(self.y))];
+
+        if let Some(string_id) = self.string_id {
+            columns.push(("string_id", Column::Integer(string_id)));
+        }
+
+        columns.push(("glyph_id", Column::Integer(self.glyph_id)));
+
+        // optional columns
+
+        if let Some(path_id) = self.path_id {
+            columns.push(("path_id", Column::Integer(path_id)));
+        }
+        if let Some(shape_id) = self.shape_id {
+            columns.push(("shape_id", Column::Integer(shape_id)));
+        }
+        if let Some(is_hole) = self.is_hole {
+            columns.push(("is_hole", Column::Logical(is_hole)));
+        }
+        if let Some(distance) = self.distance {
+            columns.push(("distance", Column::Real(distance)));
+        }
+        if let Some(curve) = self.curve {
+            columns.push(("verb", Column::StaticCharacter(curve.verb)));
+            let ctrls = [curve.ctrl1_x, curve.ctrl1_y, curve.ctrl2_x, curve.ctrl2_y];
+            for (name, ctrl) in CurveColumns::NAMES[1..].iter().zip(ctrls) {
+                columns.push((name, Column::NullableReal(ctrl)));
+            }
+        }
+        if let Some(triangle_id) = self.triangle_id {
+            columns.push(("triangle_id", Column::Integer(triangle_id)));
+        }
+        if let Some(stroke) = self.stroke_attributes {
+            columns.push(("normal_x", Column::Real(stroke.normal_x)));
+            columns.push(("normal_y", Column::Real(stroke.normal_y)));
+            columns.push(("advancement", Column::Real(stroke.advancement)));
+            columns.push(("side", Column::StaticCharacter(stroke.side)));
+        }
+        if let Some(uv) = self.uv {
+            columns.push(("u", Column::Real(uv.u)));
+            columns.push(("v", Column::Real(uv.v)));
+        }
+        if let Some(color) = self.color {
+            columns.push(("color", Column::Character(color)));
+        }
+
+        columns
+    }
+}
+
+/// A result that can be combined across multiple strings.
+pub trait StringsResult {
+    /// Set `string_id` of all the rows to 1.
+    fn init_string_id(&mut self);
+
+    /// Appends the result of another string.
+    fn append(&mut self, other: Self, string_id: i32);
+}
+
+impl StringsResult for PathTibble {
+    fn init_string_id(&mut self) {
+        self.string_id = Some(vec![1; self.x.len()]);
+    }
+
+    /// `glyph_id`, `path_id`, `shape_id`, and `triangle_id` are shifted so that
+    /// they are unique across the whole result.
+    fn append(&mut self, other: PathTibble, string_id: i32) {
+        let n = other.x.len();
+        let n_self = self.x.len();
+
+        let glyph_offset = self.glyph_id.iter().max().copied().unwrap_or(0);
+        self.glyph_id
+            .extend(other.glyph_id.iter().map(|i| i + glyph_offset));
+
+        if let (Some(dst), Some(src)) = (&mut self.path_id, other.path_id) {
+            let offset = dst.iter().max().copied().unwrap_or(0);
+            dst.extend(src.iter().map(|i| i + offset));
+        }
+
+        if let (Some(dst), Some(src)) = (&mut self.shape_id, other.shape_id) {
+            let offset = dst.iter().max().copied().unwrap_or(0);
+            dst.extend(src.iter().map(|i| i + offset));
+        }
+
+        if let (Some(dst), Some(src)) = (&mut self.is_hole, other.is_hole) {
+            dst.extend(src);
+        }
+
+        if let (Some(dst), Some(src)) = (&mut self.distance, other.distance) {
+            dst.extend(src);
+        }
+
+        if let (Some(dst), Some(src)) = (&mut self.curve, other.curve) {
+            dst.extend(src);
+        }
+
+        if let (Some(dst), Some(src)) = (&mut self.triangle_id, other.triangle_id) {
+            let offset = dst.iter().max().map_or(0, |i| i + 1);
+            dst.extend(src.iter().map(|i| i + offset));
+        }
+
+        if let (Some(dst), Some(src)) = (&mut self.stroke_attributes, other.stroke_attributes) {
+            dst.extend(src);
+        }
+
+        if let (Some(dst), Some(src)) = (&mut self.uv, other.uv) {
+            dst.extend(src);
+        }
+
+        // Non-COLR glyphs in mixed text default to opaque black.
+        match (&mut self.color, other.color) {
+            (Some(dst), Some(src)) => dst.extend(src),
+            (Some(dst), None) => dst.extend(std::iter::repeat_n(color_to_hex(None), n)),
+            (None, Some(src)) => {
+                let mut color: Vec<String> =
+                    std::iter::repeat_n(color_to_hex(None), n_self).collect();
+                color.extend(src);
+                self.color = Some(color);
+            }
+            (None, None) => {}
+        }
+
+        if let Some(dst) = &mut self.string_id {
+            dst.extend(std::iter::repeat_n(string_id, n));
+        }
+
+        self.x.extend(other.x);
+        self.y.extend(other.y);
     }
 }
 
@@ -36,35 +267,151 @@ impl TryFrom<PathTibble> for savvy::Sexp {
     type Error = savvy::Error;
 
     fn try_from(value: PathTibble) -> savvy::Result<Self> {
-        let mut out = savvy::OwnedListSexp::new(value.len(), true)?;
+        let columns = value.into_columns();
+        let mut out = savvy::OwnedListSexp::new(columns.len(), true)?;
 
-        out.set_name_and_value(0, "x", <OwnedRealSexp>::try_from(value.x.as_slice())?)?;
-        out.set_name_and_value(1, "y", <OwnedRealSexp>::try_from(value.y.as_slice())?)?;
-        out.set_name_and_value(
-            2,
-            "glyph_id",
-            <OwnedIntegerSexp>::try_from(value.glyph_id.as_slice())?,
-        )?;
+        for (i, (name, column)) in columns.into_iter().enumerate() {
+            out.set_name_and_value(i, name, savvy::Sexp::try_from(column)?)?;
+        }
 
-        // optional columns
-        let mut idx = 2;
+        out.into()
+    }
+}
 
-        if let Some(path_id) = value.path_id {
-            idx += 1;
-            let v = <OwnedIntegerSexp>::try_from(path_id.as_slice())?;
-            out.set_name_and_value(idx, "path_id", v)?;
+/// An intermediate form of an indexed mesh to convert to two tibbles; one is
+/// the vertices, and the other is the triangles that refer to the vertices by
+/// the 1-based row numbers.
+pub struct MeshTibble {
+    // Position of the vertices.
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    // IDs to distinguish the strings. This field is `None` when the input is a single string.
+    pub string_id: Option<Vec<i32>>,
+    // IDs to distinguish the glyphs of the vertices.
+    pub glyph_id: Vec<i32>,
+    // Color of color emoji font.
+    pub color: Option<Vec<String>>,
+
+    // Indices of the vertices of the triangles.
+    pub i: Vec<i32>,
+    pub j: Vec<i32>,
+    pub k: Vec<i32>,
+    // IDs to distinguish the strings of the triangles.
+    pub triangle_string_id: Option<Vec<i32>>,
+    // IDs to distinguish the glyphs of the triangles.
+    pub triangle_glyph_id: Vec<i32>,
+}
+
+impl MeshTibble {
+    pub fn new(has_color: bool) -> Self {
+        Self {
+            x: Vec::new(),
+            y: Vec::new(),
+            string_id: None,
+            glyph_id: Vec::new(),
+            color: if has_color { Some(Vec::new()) } else { None },
+            i: Vec::new(),
+            j: Vec::new(),
+            k: Vec::new(),
+            triangle_string_id: None,
+            triangle_glyph_id: Vec::new(),
         }
-        if let Some(triangle_id) = value.triangle_id {
+    }
+}
+
+impl StringsResult for MeshTibble {
+    fn init_string_id(&mut self) {
+        self.string_id = Some(vec![1; self.x.len()]);
+        self.triangle_string_id = Some(vec![1; self.i.len()]);
+    }
+
+    /// `glyph_id` and the indices of the vertices are shifted so that they are
+    /// unique across the whole result.
+    fn append(&mut self, other: Self, string_id: i32) {
+        let n = other.x.len();
+        let n_self = self.x.len();
+        let n_triangles = other.i.len();
+
+        let glyph_offset = self.glyph_id.iter().max().copied().unwrap_or(0);
+        self.glyph_id
+            .extend(other.glyph_id.iter().map(|i| i + glyph_offset));
+        self.triangle_glyph_id
+            .extend(other.triangle_glyph_id.iter().map(|i| i + glyph_offset));
+
+        let index_offset = n_self as i32;
+        self.i.extend(other.i.iter().map(|i| i + index_offset));
+        self.j.extend(other.j.iter().map(|i| i + index_offset));
+        self.k.extend(other.k.iter().map(|i| i + index_offset));
+
+        // Non-COLR glyphs in mixed text default to opaque black.
+        match (&mut self.color, other.color) {
+            (Some(dst), Some(src)) => dst.extend(src),
+            (Some(dst), None) => dst.extend(std::iter::repeat_n(color_to_hex(None), n)),
+            (None, Some(src)) => {
+                let mut color: Vec<String> =
+                    std::iter::repeat_n(color_to_hex(None), n_self).collect();
+                color.extend(src);
+                self.color = Some(color);
+            }
+            (None, None) => {}
+        }
+
+        if let Some(dst) = &mut self.string_id {
+            dst.extend(std::iter::repeat_n(string_id, n));
+        }
+        if let Some(dst) = &mut self.triangle_string_id {
+            dst.extend(std::iter::repeat_n(string_id, n_triangles));
+        }
+
+        self.x.extend(other.x);
+        self.y.extend(other.y);
+    }
+}
+
+impl TryFrom<MeshTibble> for savvy::Sexp {
+    type Error = savvy::Error;
+
+    fn try_from(value: MeshTibble) -> savvy::Result<Self> {
+        let len = 3 + value.string_id.is_some() as usize + value.color.is_some() as usize;
+        let mut vertices = savvy::OwnedListSexp::new(len, true)?;
+
+        vertices.set_name_and_value(0, "x", <OwnedRealSexp>::try_from(value.x.as_slice())?)?;
+        vertices.set_name_and_value(1, "y", <OwnedRealSexp>::try_from(value.y.as_slice())?)?;
+
+        let mut idx = 2;
+        if let Some(string_id) = value.string_id {
+            let v = <OwnedIntegerSexp>::try_from(string_id.as_slice())?;
+            vertices.set_name_and_value(idx, "string_id", v)?;
             idx += 1;
-            let v = <OwnedIntegerSexp>::try_from(triangle_id.as_slice())?;
-            out.set_name_and_value(idx, "triangle_id", v)?;
         }
+        let v = <OwnedIntegerSexp>::try_from(value.glyph_id.as_slice())?;
+        vertices.set_name_and_value(idx, "glyph_id", v)?;
+        idx += 1;
         if let Some(color) = value.color {
-            idx += 1;
             let v = <OwnedStringSexp>::try_from(color.as_slice())?;
-            out.set_name_and_value(idx, "color", v)?;
+            vertices.set_name_and_value(idx, "color", v)?;
         }
 
+        let len = 4 + value.triangle_string_id.is_some() as usize;
+        let mut triangles = savvy::OwnedListSexp::new(len, true)?;
+
+        triangles.set_name_and_value(0, "i", <OwnedIntegerSexp>::try_from(value.i.as_slice())?)?;
+        triangles.set_name_and_value(1, "j", <OwnedIntegerSexp>::try_from(value.j.as_slice())?)?;
+        triangles.set_name_and_value(2, "k", <OwnedIntegerSexp>::try_from(value.k.as_slice())?)?;
+
+        let mut idx = 3;
+        if let Some(string_id) = value.triangle_string_id {
+            let v = <OwnedIntegerSexp>::try_from(string_id.as_slice())?;
+            triangles.set_name_and_value(idx, "string_id", v)?;
+            idx += 1;
+        }
+        let v = <OwnedIntegerSexp>::try_from(value.triangle_glyph_id.as_slice())?;
+        triangles.set_name_and_value(idx, "glyph_id", v)?;
+
+        let mut out = savvy::OwnedListSexp::new(2, true)?;
+        out.set_name_and_value(0, "vertices", vertices)?;
+        out.set_name_and_value(1, "triangles", triangles)?;
+
         out.into()
     }
 }
@@ -0,0 +1,489 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::builder::color_to_hex;
+use crate::into_path::{GlyphShapes, Shapes};
+use crate::result::PathTibble;
+
+// The maximum number of pixels of the raster of a glyph.
+const MAX_PIXELS: usize = 50_000_000;
+
+/// Parameters of the skeleton extraction.
+#[derive(Clone, Copy)]
+pub struct SkeletonOptions {
+    // The size of a pixel of the raster.
+    pub resolution: f32,
+    // The branches shorter than this are removed if they end at a junction.
+    pub prune: f32,
+    // The tolerance of Douglas-Peucker simplification.
+    pub simplify: f32,
+}
+
+/// A binary image of a glyph. The pixel (i, j) covers the area from
+/// (x0 + i * resolution, y0 + j * resolution) to (x0 + (i + 1) * resolution,
+/// y0 + (j + 1) * resolution).
+struct Raster {
+    width: usize,
+    height: usize,
+    x0: f32,
+    y0: f32,
+    resolution: f32,
+    pixels: Vec<bool>,
+}
+
+impl Raster {
+    /// Rasterizes the shapes by the even-odd rule, sampling the center of each
+    /// pixel. The shapes must not overlap each other.
+    fn new(shapes: &Shapes, resolution: f32) -> savvy::Result<Self> {
+        let points = shapes.iter().flatten().flatten();
+        let (mut x_min, mut y_min) = (f32::INFINITY, f32::INFINITY);
+        let (mut x_max, mut y_max) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for &[x, y] in points {
+            x_min = x_min.min(x);
+            y_min = y_min.min(y);
+            x_max = x_max.max(x);
+            y_max = y_max.max(y);
+        }
+
+        // Pad with 1 pixel so that the neighbors of the filled pixels are
+        // always inside the raster.
+        let x0 = x_min - resolution;
+        let y0 = y_min - resolution;
+        let width = ((x_max - x0) / resolution).ceil() as usize + 2;
+        let height = ((y_max - y0) / resolution).ceil() as usize + 2;
+        if width.saturating_mul(height) > MAX_PIXELS {
+            return Err(savvy::Error::new(
+                "The resolution is too small for the size of the glyph",
+            ));
+        }
+
+        let mut pixels = vec![false; width * height];
+        let mut crossings: Vec<f32> = Vec::new();
+        for j in 0..height {
+            let y = y0 + (j as f32 + 0.5) * resolution;
+
+            crossings.clear();
+            for contour in shapes.iter().flatten() {
+                let next = contour.iter().skip(1).chain(contour.first());
+                for (a, b) in contour.iter().zip(next) {
+                    if (a[1] <= y) != (b[1] <= y) {
+                        let t = (y - a[1]) / (b[1] - a[1]);
+                        crossings.push(a[0] + (b[0] - a[0]) * t);
+                    }
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                // The pixels whose centers are inside the span
+                let i_start = ((span[0] - x0) / resolution - 0.5).ceil().max(0.) as usize;
+                let i_end = ((span[1] - x0) / resolution + 0.5)
+                    .floor()
+                    .clamp(0., width as f32) as usize;
+                for i in i_start..i_end {
+                    pixels[j * width + i] = true;
+                }
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            x0,
+            y0,
+            resolution,
+            pixels,
+        })
+    }
+
+    fn get(&self, i: isize, j: isize) -> bool {
+        i >= 0
+            && j >= 0
+            && (i as usize) < self.width
+            && (j as usize) < self.height
+            && self.pixels[j as usize * self.width + i as usize]
+    }
+
+    /// Returns the 8 neighbors P2, ..., P9 of the pixel in the clockwise order,
+    /// starting from the one above.
+    fn neighbors(&self, idx: usize) -> [bool; 8] {
+        let (i, j) = ((idx % self.width) as isize, (idx / self.width) as isize);
+        [
+            self.get(i, j + 1),
+            self.get(i + 1, j + 1),
+            self.get(i + 1, j),
+            self.get(i + 1, j - 1),
+            self.get(i, j - 1),
+            self.get(i - 1, j - 1),
+            self.get(i - 1, j),
+            self.get(i - 1, j + 1),
+        ]
+    }
+
+    /// Returns the indices of the filled pixels among the 8 neighbors.
+    fn neighbor_indices(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let (i, j) = ((idx % self.width) as isize, (idx / self.width) as isize);
+        [
+            (0, 1),
+            (1, 1),
+            (1, 0),
+            (1, -1),
+            (0, -1),
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+        ]
+        .into_iter()
+        .filter(move |(di, dj)| self.get(i + di, j + dj))
+        .map(move |(di, dj)| (j + dj) as usize * self.width + (i + di) as usize)
+    }
+
+    fn degree(&self, idx: usize) -> usize {
+        self.neighbor_indices(idx).count()
+    }
+
+    fn to_point(&self, idx: usize) -> [f32; 2] {
+        let (i, j) = (idx % self.width, idx / self.width);
+        [
+            self.x0 + (i as f32 + 0.5) * self.resolution,
+            self.y0 + (j as f32 + 0.5) * self.resolution,
+        ]
+    }
+
+    /// Thins the filled area into 1-pixel wide lines by Zhang-Suen algorithm.
+    fn thin(&mut self) {
+        // Only the filled pixels need to be checked.
+        let mut filled: Vec<usize> = (0..self.pixels.len()).filter(|&i| self.pixels[i]).collect();
+        let mut to_remove = Vec::new();
+        loop {
+            let mut changed = false;
+            for step in 0..2 {
+                to_remove.clear();
+                for &idx in &filled {
+                    let p = self.neighbors(idx);
+                    let b = p.iter().filter(|&&v| v).count();
+                    // The number of 0 -> 1 transitions in P2, P3, ..., P9, P2
+                    let a = (0..8).filter(|&k| !p[k] && p[(k + 1) % 8]).count();
+                    let [p2, _, p4, _, p6, _, p8, _] = p;
+                    let cond = if step == 0 {
+                        !(p4 && p6 && (p2 || p8))
+                    } else {
+                        !(p2 && p8 && (p4 || p6))
+                    };
+                    if (2..=6).contains(&b) && a == 1 && cond {
+                        to_remove.push(idx);
+                    }
+                }
+                for &idx in &to_remove {
+                    self.pixels[idx] = false;
+                }
+                filled.retain(|&idx| self.pixels[idx]);
+                changed |= !to_remove.is_empty();
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // Zhang-Suen leaves the staircases, which make the pixels look like
+        // junctions. Remove the redundant pixels whose removal doesn't change
+        // the connectivity, except for the end points.
+        for idx in filled {
+            if is_redundant(&self.neighbors(idx)) {
+                self.pixels[idx] = false;
+            }
+        }
+    }
+}
+
+/// Whether the pixel has 2 or more neighbors and its 8-connectivity number
+/// (Yokoi) is 1, i.e., the neighbors stay connected without the pixel.
+fn is_redundant(p: &[bool; 8]) -> bool {
+    let n_neighbors = p.iter().filter(|&&v| v).count();
+    let connectivity: usize = [0, 2, 4, 6]
+        .into_iter()
+        .filter(|&k| !p[k] && (p[k + 1] || p[(k + 2) % 8]))
+        .count();
+    n_neighbors >= 2 && connectivity == 1
+}
+
+/// A polyline of pixels between two nodes, or a loop.
+type Edge = Vec<usize>;
+
+/// Traces the skeleton pixels into the polylines between the nodes (the end
+/// points and the junctions). The loops without nodes are also returned.
+fn trace(raster: &Raster) -> Vec<Edge> {
+    let is_node = |idx: usize| raster.degree(idx) != 2;
+    let key = |a: usize, b: usize| (a.min(b), a.max(b));
+
+    let mut edges = Vec::new();
+    let mut visited_links: HashSet<(usize, usize)> = HashSet::new();
+    let mut visited_pixels = vec![false; raster.pixels.len()];
+
+    for start in 0..raster.pixels.len() {
+        if !raster.pixels[start] || !is_node(start) {
+            continue;
+        }
+        visited_pixels[start] = true;
+
+        // An isolated pixel, e.g. a period.
+        if raster.degree(start) == 0 {
+            edges.push(vec![start]);
+            continue;
+        }
+
+        for next in raster.neighbor_indices(start).collect::<Vec<_>>() {
+            if !visited_links.insert(key(start, next)) {
+                continue;
+            }
+            let mut edge = vec![start, next];
+            let (mut prev, mut cur) = (start, next);
+            while !is_node(cur) {
+                visited_pixels[cur] = true;
+                let Some(following) = raster
+                    .neighbor_indices(cur)
+                    .find(|&n| n != prev && !visited_links.contains(&key(cur, n)))
+                else {
+                    break;
+                };
+                visited_links.insert(key(cur, following));
+                edge.push(following);
+                (prev, cur) = (cur, following);
+            }
+            edges.push(edge);
+        }
+    }
+
+    // The remaining pixels form the loops, e.g. the skeleton of "O".
+    for start in 0..raster.pixels.len() {
+        if !raster.pixels[start] || visited_pixels[start] {
+            continue;
+        }
+        let mut edge = vec![start];
+        let (mut prev, mut cur) = (usize::MAX, start);
+        loop {
+            visited_pixels[cur] = true;
+            let Some(following) = raster
+                .neighbor_indices(cur)
+                .find(|&n| n != prev && !visited_links.contains(&key(cur, n)))
+            else {
+                break;
+            };
+            visited_links.insert(key(cur, following));
+            edge.push(following);
+            if following == start {
+                break;
+            }
+            (prev, cur) = (cur, following);
+        }
+        edges.push(edge);
+    }
+
+    edges
+}
+
+/// Merges the adjacent junction pixels into one node, and drops the tiny
+/// edges between them. Otherwise, a junction often looks like a cluster of
+/// junctions connected with each other.
+fn merge_junctions(raster: &Raster, edges: Vec<Edge>) -> Vec<Edge> {
+    let is_junction = |idx: usize| raster.degree(idx) >= 3;
+
+    let mut representative: HashMap<usize, usize> = HashMap::new();
+    for start in 0..raster.pixels.len() {
+        if !raster.pixels[start] || !is_junction(start) || representative.contains_key(&start) {
+            continue;
+        }
+        let mut stack = vec![start];
+        representative.insert(start, start);
+        while let Some(idx) = stack.pop() {
+            for n in raster.neighbor_indices(idx) {
+                if is_junction(n) && !representative.contains_key(&n) {
+                    representative.insert(n, start);
+                    stack.push(n);
+                }
+            }
+        }
+    }
+
+    edges
+        .into_iter()
+        .filter_map(|mut edge| {
+            let last = edge.len() - 1;
+            for i in [0, last] {
+                if let Some(&r) = representative.get(&edge[i]) {
+                    edge[i] = r;
+                }
+            }
+            edge.dedup();
+            // A link within the cluster
+            let is_link = edge.len() <= 3 && edge[0] == edge[edge.len() - 1];
+            (!is_link || last == 0).then_some(edge)
+        })
+        .collect()
+}
+
+fn edge_length(raster: &Raster, edge: &Edge) -> f32 {
+    edge.windows(2)
+        .map(|w| {
+            let (a, b) = (raster.to_point(w[0]), raster.to_point(w[1]));
+            (b[0] - a[0]).hypot(b[1] - a[1])
+        })
+        .sum()
+}
+
+/// Removes the branches shorter than `prune` that connect an end point and a
+/// junction, from the shortest one. The edges at a node that is no longer a
+/// junction are joined into one.
+fn prune(raster: &Raster, mut edges: Vec<Edge>, prune: f32) -> Vec<Edge> {
+    let degrees = |edges: &[Edge]| {
+        let mut degrees: BTreeMap<usize, usize> = BTreeMap::new();
+        for edge in edges.iter().filter(|e| e.len() > 1) {
+            *degrees.entry(edge[0]).or_default() += 1;
+            *degrees.entry(edge[edge.len() - 1]).or_default() += 1;
+        }
+        degrees
+    };
+
+    loop {
+        let degree = degrees(&edges);
+        let is_spur = |edge: &Edge| {
+            let (a, b) = (edge[0], edge[edge.len() - 1]);
+            a != b && ((degree[&a] == 1 && degree[&b] >= 3) || (degree[&b] == 1 && degree[&a] >= 3))
+        };
+        let shortest = edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| edge.len() > 1 && is_spur(edge))
+            .map(|(i, edge)| (i, edge_length(raster, edge)))
+            .filter(|&(_, len)| len < prune)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((i, _)) = shortest else {
+            break;
+        };
+
+        let removed = edges.remove(i);
+        let junction = if degree[&removed[0]] == 1 {
+            removed[removed.len() - 1]
+        } else {
+            removed[0]
+        };
+
+        // Join the remaining two edges at the junction if any.
+        let touching: Vec<usize> = edges
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.len() > 1 && (e[0] == junction || e[e.len() - 1] == junction))
+            .map(|(i, _)| i)
+            .collect();
+        if let [i1, i2] = touching[..] {
+            let mut e2 = edges.remove(i2);
+            let mut e1 = edges.remove(i1);
+            if e1[0] == junction {
+                e1.reverse();
+            }
+            if e2[e2.len() - 1] == junction {
+                e2.reverse();
+            }
+            e1.extend(e2.into_iter().skip(1));
+            edges.push(e1);
+        }
+    }
+
+    edges
+}
+
+/// Simplifies the polyline by Douglas-Peucker algorithm.
+fn simplify(points: &[[f32; 2]], tolerance: f32) -> Vec<[f32; 2]> {
+    if points.len() <= 2 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let (a, b) = (points[start], points[end]);
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let len = dx.hypot(dy);
+        let distance = |p: [f32; 2]| {
+            if len == 0. {
+                // A loop; measure the distance from the start point.
+                (p[0] - a[0]).hypot(p[1] - a[1])
+            } else {
+                ((p[0] - a[0]) * dy - (p[1] - a[1]) * dx).abs() / len
+            }
+        };
+
+        let farthest = (start + 1..end)
+            .map(|i| (i, distance(points[i])))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((i, d)) = farthest
+            && d > tolerance
+        {
+            keep[i] = true;
+            stack.push((start, i));
+            stack.push((i, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(p, k)| k.then_some(*p))
+        .collect()
+}
+
+/// Extracts the skeletons (the centerlines) of the glyphs as open or closed
+/// polylines.
+pub fn shapes_to_skeleton(
+    glyph_shapes: GlyphShapes,
+    has_color: bool,
+    options: SkeletonOptions,
+) -> savvy::Result<PathTibble> {
+    let mut x = Vec::new();
+    let mut y = Vec::new();
+    let mut glyph_id = Vec::new();
+    let mut path_id = Vec::new();
+    let mut color_vec: Vec<String> = Vec::new();
+    let mut out_path_id = 0;
+
+    for (gid, shapes, paint_color) in glyph_shapes {
+        let mut raster = Raster::new(&shapes, options.resolution)?;
+        raster.thin();
+        let edges = merge_junctions(&raster, trace(&raster));
+        let edges = prune(&raster, edges, options.prune);
+
+        for edge in edges {
+            let points: Vec<[f32; 2]> = edge.iter().map(|&idx| raster.to_point(idx)).collect();
+            let points = simplify(&points, options.simplify);
+
+            out_path_id += 1;
+            for [px, py] in points {
+                x.push(px as f64);
+                y.push(py as f64);
+                glyph_id.push(gid as i32);
+                path_id.push(out_path_id);
+                if has_color {
+                    color_vec.push(color_to_hex(paint_color));
+                }
+            }
+        }
+    }
+
+    Ok(PathTibble {
+        x,
+        y,
+        string_id: None,
+        glyph_id,
+        path_id: Some(path_id),
+        shape_id: None,
+        is_hole: None,
+        distance: None,
+        curve: None,
+        triangle_id: None,
+        stroke_attributes: None,
+        uv: None,
+        color: if has_color { Some(color_vec) } else { None },
+    })
+}
@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use lyon::path::{Event, Path};
+
+use crate::builder::{GlyphPaths, RgbaColor};
+
+/// Formats a number compactly; trailing zeros are removed.
+fn fmt_num(v: f32) -> String {
+    let s = format!("{v:.5}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" | "" => "0".to_string(),
+        _ => s.to_string(),
+    }
+}
+
+/// Converts the path into the path data (the `d` attribute) of SVG. Since the
+/// y-axis of SVG points downward, y is flipped.
+fn to_path_data(path: &Path) -> String {
+    let mut d = String::new();
+    let p = |pt: lyon::math::Point| format!("{} {}", fmt_num(pt.x), fmt_num(-pt.y));
+
+    for event in path.iter() {
+        match event {
+            Event::Begin { at } => write!(d, "M{}", p(at)),
+            Event::Line { to, .. } => write!(d, "L{}", p(to)),
+            Event::Quadratic { ctrl, to, .. } => write!(d, "Q{} {}", p(ctrl), p(to)),
+            Event::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => write!(d, "C{} {} {}", p(ctrl1), p(ctrl2), p(to)),
+            Event::End { close: true, .. } => write!(d, "Z"),
+            Event::End { close: false, .. } => Ok(()),
+        }
+        .unwrap();
+    }
+
+    d
+}
+
+/// Returns the `fill` (and `fill-opacity`) attributes for the COLR color.
+fn to_fill_attr(color: Option<RgbaColor>) -> String {
+    match color {
+        Some(RgbaColor {
+            red,
+            green,
+            blue,
+            alpha,
+        }) => {
+            let mut attr = format!(r##" fill="#{red:02x}{green:02x}{blue:02x}""##);
+            if alpha != 255 {
+                write!(attr, r#" fill-opacity="{}""#, fmt_num(alpha as f32 / 255.)).unwrap();
+            }
+            attr
+        }
+        None => String::new(),
+    }
+}
+
+/// Writes the outlines of the strings as an SVG document.
+///
+/// Each string is a `<g>` element, and each glyph in it is a `<path>` element
+/// with the original quadratic and cubic Bézier segments. A COLR glyph that
+/// consists of multiple layers is a `<g>` element of the `<path>`s of the
+/// layers. `size` is the number of pixels per line height.
+pub fn write_svg(strings: &[GlyphPaths], size: f32) -> String {
+    let bbox = strings
+        .iter()
+        .flatten()
+        .map(|(_, path, _)| lyon::algorithms::aabb::bounding_box(path.iter()))
+        .reduce(|a, b| a.union(&b));
+
+    // Note that y is flipped
+    let (min_x, min_y, width, height) = match bbox {
+        Some(b) => (b.min.x, -b.max.y, b.width(), b.height()),
+        None => (0., 0., 0., 0.),
+    };
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        fmt_num((width * size * 100.).round() / 100.),
+        fmt_num((height * size * 100.).round() / 100.),
+        fmt_num(min_x),
+        fmt_num(min_y),
+        fmt_num(width),
+        fmt_num(height),
+    )
+    .unwrap();
+
+    for (i, glyph_paths) in strings.iter().enumerate() {
+        writeln!(out, r#"  <g id="string-{}">"#, i + 1).unwrap();
+
+        // The layers of a COLR glyph share the same glyph ID.
+        for layers in glyph_paths.chunk_by(|a, b| a.0 == b.0) {
+            let glyph_id = layers[0].0;
+            if let [(_, path, color)] = layers {
+                writeln!(
+                    out,
+                    r#"    <path data-glyph-id="{glyph_id}"{} d="{}"/>"#,
+                    to_fill_attr(*color),
+                    to_path_data(path)
+                )
+                .unwrap();
+            } else {
+                writeln!(out, r#"    <g data-glyph-id="{glyph_id}">"#).unwrap();
+                for (_, path, color) in layers {
+                    writeln!(
+                        out,
+                        r#"      <path{} d="{}"/>"#,
+                        to_fill_attr(*color),
+                        to_path_data(path)
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "    </g>").unwrap();
+            }
+        }
+
+        writeln!(out, "  </g>").unwrap();
+    }
+
+    writeln!(out, "</svg>").unwrap();
+    out
+}